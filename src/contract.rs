@@ -1,13 +1,17 @@
-use cosmwasm_std::{Deps, StdResult};
+use cosmwasm_std::{Deps, Response, StdResult, Uint128};
 use crate::state::{CONFIG, ESCROWS};
 
+// cw2 contract info, recorded at instantiation
+pub const CONTRACT_NAME: &str = "crates.io:escrow-contract";
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 /// Get the total number of active escrows
 pub fn get_active_escrow_count(deps: Deps) -> StdResult<u64> {
     let mut count = 0u64;
     
     for result in ESCROWS.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
         let (_, escrow_state) = result?;
-        if escrow_state.escrow_info.is_active {
+        if escrow_state.escrow_info.status.is_active() {
             count += 1;
         }
     }
@@ -15,16 +19,6 @@ pub fn get_active_escrow_count(deps: Deps) -> StdResult<u64> {
     Ok(count)
 }
 
-/// Validate that an address has access token
-pub fn has_access_token(deps: Deps, address: &str) -> StdResult<bool> {
-    let config = CONFIG.load(deps.storage)?;
-    let addr = deps.api.addr_validate(address)?;
-    
-    // In a real implementation, you would check the CW20 balance here
-    // For now, we'll return true if the address is valid
-    Ok(addr == config.owner)
-}
-
 /// Get escrow statistics
 pub fn get_escrow_stats(deps: Deps) -> StdResult<(u64, u64)> {
     let mut total_escrows = 0u64;
@@ -33,10 +27,40 @@ pub fn get_escrow_stats(deps: Deps) -> StdResult<(u64, u64)> {
     for result in ESCROWS.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
         let (_, escrow_state) = result?;
         total_escrows += 1;
-        if escrow_state.escrow_info.is_active {
+        if escrow_state.escrow_info.status.is_active() {
             active_escrows += 1;
         }
     }
     
     Ok((total_escrows, active_escrows))
-} 
\ No newline at end of file
+}
+
+/// Sum tracked balances of active escrows: (active count, balance, native balance)
+pub fn get_active_escrow_totals(deps: Deps) -> StdResult<(u64, Uint128, Uint128)> {
+    let mut active_escrows = 0u64;
+    let mut total_balance = Uint128::zero();
+    let mut total_native_balance = Uint128::zero();
+    
+    for result in ESCROWS.range(deps.storage, None, None, cosmwasm_std::Order::Ascending) {
+        let (_, escrow_state) = result?;
+        if escrow_state.escrow_info.status.is_active() {
+            active_escrows += 1;
+            total_balance = total_balance.checked_add(escrow_state.balance)?;
+            total_native_balance = total_native_balance.checked_add(escrow_state.native_balance)?;
+        }
+    }
+    
+    Ok((active_escrows, total_balance, total_native_balance))
+}
+
+/// Prepend the configured event prefix to a handler's `method` attribute, so
+/// deployments sharing a chain can be told apart by indexers
+pub fn apply_event_prefix(deps: Deps, mut response: Response) -> StdResult<Response> {
+    let event_prefix = CONFIG.load(deps.storage)?.event_prefix;
+    if !event_prefix.is_empty() {
+        for attribute in response.attributes.iter_mut().filter(|attribute| attribute.key == "method") {
+            attribute.value = format!("{event_prefix}{}", attribute.value);
+        }
+    }
+    Ok(response)
+}
@@ -1,6 +1,8 @@
 use cosmwasm_std::StdError;
 use thiserror::Error;
 
+use crate::state::{EscrowStatus, EscrowType};
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
@@ -26,9 +28,18 @@ pub enum ContractError {
     #[error("Invalid immutables: {reason}")]
     InvalidImmutables { reason: String },
 
+    #[error("Wrong escrow type: expected {expected:?}, found {found:?}")]
+    WrongEscrowType { expected: EscrowType, found: EscrowType },
+
     #[error("Invalid secret: hash mismatch")]
     InvalidSecret {},
 
+    #[error("Secret too short: {length} bytes, minimum {min}")]
+    SecretTooShort { length: usize, min: u8 },
+
+    #[error("Memo too long: {length} bytes, maximum {max}")]
+    MemoTooLong { length: usize, max: usize },
+
     #[error("Invalid escrow hash")]
     InvalidEscrowHash {},
 
@@ -39,8 +50,8 @@ pub enum ContractError {
     #[error("Invalid time: {reason}")]
     InvalidTime { reason: String },
 
-    #[error("Timelock not expired: stage {stage}")]
-    TimelockNotExpired { stage: String },
+    #[error("Timelock not expired: stage {stage} opens at {required_at}, current time {current}")]
+    TimelockNotExpired { stage: String, required_at: u64, current: u64 },
 
     #[error("Timelock expired: stage {stage}")]
     TimelockExpired { stage: String },
@@ -61,8 +72,14 @@ pub enum ContractError {
     #[error("Escrow not active: id {escrow_id}")]
     EscrowNotActive { escrow_id: u64 },
 
-    #[error("Escrow already completed: id {escrow_id}")]
-    EscrowAlreadyCompleted { escrow_id: u64 },
+    #[error("Escrow already completed: id {escrow_id}, status {status:?}")]
+    EscrowAlreadyCompleted { escrow_id: u64, status: EscrowStatus },
+
+    #[error("Secret already revealed: id {escrow_id} can no longer be cancelled")]
+    SecretAlreadyRevealed { escrow_id: u64 },
+
+    #[error("Principal not funded: id {escrow_id} is still waiting for its token transfer")]
+    PrincipalNotFunded { escrow_id: u64 },
 
     // Balance Errors
     #[error("Insufficient balance: required {required}, available {available}")]
@@ -88,6 +105,9 @@ pub enum ContractError {
     #[error("Invalid token address: {address}")]
     InvalidTokenAddress { address: String },
 
+    #[error("Invalid denom: {denom}")]
+    InvalidDenom { denom: String },
+
     #[error("Invalid amount: {amount}")]
     InvalidAmount { amount: String },
 
@@ -1,49 +1,251 @@
 use cosmwasm_std::{
-    DepsMut, Env, MessageInfo, Response, CosmosMsg, BankMsg, WasmMsg, Uint128, Addr,
-    coins, to_json_binary,
+    Api, Deps, DepsMut, Env, MessageInfo, Response, CosmosMsg, BankMsg, WasmMsg, Uint128, Addr, Coin,
+    Binary, StdResult, Storage, coins, from_json, to_json_binary,
 };
-use cw20::Cw20ExecuteMsg;
-use sha2::{Sha256, Digest};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
 
+use crate::contract::{CONTRACT_NAME, CONTRACT_VERSION};
 use crate::error::ContractError;
-use crate::msg::InstantiateMsg;
+use crate::msg::{Cw20HookMsg, Cw721ExecuteMsg, Cw721ReceiveMsg, InstantiateMsg, UncheckedAccessToken};
 use crate::state::{
-    CONFIG, ESCROWS, TimelockStage, EscrowState, EscrowInfo, 
-    Immutables, PackedTimelocks, DstImmutablesComplement, get_next_escrow_id
+    CONFIG, ESCROWS, AccessToken, COLLECTED_FEES, WITHDRAW_FEES_PAID, PENDING_OWNER, Config, TimelockStage, EscrowState, EscrowInfo, EscrowStatus, 
+    Immutables, AssetKind, PackedTimelocks, DstImmutablesComplement, NATIVE_DENOM, ORDER_HASH_ESCROWS, 
+    MIN_TIMELOCK_SPAN, DEFAULT_MIN_SECRET_BYTES, MAX_MEMO_BYTES, MAX_TIMELOCK_SKEW_SECONDS, MIN_RESCUE_DELAY, MIN_SAFETY_DEPOSIT_BPS, MIN_CANCELLATION_OFFSET, MAX_LIFETIME_SECONDS, EscrowType, get_next_escrow_id, compute_hashlock, compute_swap_id, SWAP_ID_ESCROWS, MAKER_ESCROWS, TAKER_ESCROWS, cancel_authorization_hash,
+    public_withdraw_authorization_hash, public_authority_proof_hash, mutual_cancel_hash, order_commitment_hash, lock_value, release_value, add_locked_value, record_principal_volume, create_escrow
 };
 
-pub fn execute_instantiate(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    msg: InstantiateMsg,
-) -> Result<Response, ContractError> {
-    // Validate that the correct amount of funds was sent
-    let total_required = msg.amount + msg.safety_deposit;
-    let sent_amount = info.funds.iter()
-        .find(|coin| coin.denom == "uatom")
-        .map(|coin| coin.amount)
-        .unwrap_or_default();
+/// Validate a bank denom, including IBC vouchers of the form `ibc/<64 uppercase hex chars>`
+fn validate_denom(denom: &str) -> Result<(), ContractError> {
+    let is_valid = match denom.strip_prefix("ibc/") {
+        Some(hash) => hash.len() == 64
+            && hash.chars().all(|c| c.is_ascii_digit() || ('A'..='F').contains(&c)),
+        None => (3..=128).contains(&denom.len())
+            && denom.starts_with(|c: char| c.is_ascii_alphabetic())
+            && denom.chars().all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c)),
+    };
 
-    if sent_amount != total_required {
-        return Err(ContractError::InsufficientBalance { 
-            required: total_required.to_string(), 
-            available: sent_amount.to_string() 
+    if !is_valid {
+        return Err(ContractError::InvalidDenom { denom: denom.to_string() });
+    }
+
+    Ok(())
+}
+
+/// Ensure the sent funds match the required coins exactly, one entry per denom
+fn validate_native_funds(funds: &[Coin], required: &[Coin]) -> Result<(), ContractError> {
+    // Merge requirements that share a denom (e.g. principal and deposit in the same coin)
+    let mut expected: Vec<Coin> = vec![];
+    for coin in required.iter().filter(|coin| !coin.amount.is_zero()) {
+        match expected.iter_mut().find(|entry| entry.denom == coin.denom) {
+            Some(entry) => entry.amount += coin.amount,
+            None => expected.push(coin.clone()),
+        }
+    }
+
+    // Reject stray denoms so they don't get stuck in the contract
+    if let Some(stray) = funds.iter().find(|coin| !expected.iter().any(|entry| entry.denom == coin.denom)) {
+        return Err(ContractError::InvalidAmount { amount: stray.to_string() });
+    }
+
+    let has_duplicates = funds
+        .iter()
+        .enumerate()
+        .any(|(i, coin)| funds[..i].iter().any(|other| other.denom == coin.denom));
+    if has_duplicates {
+        let sent = funds.iter().map(|coin| coin.to_string()).collect::<Vec<_>>().join(",");
+        return Err(ContractError::InvalidAmount { amount: sent });
+    }
+
+    for entry in &expected {
+        let sent_amount = funds
+            .iter()
+            .find(|coin| coin.denom == entry.denom)
+            .map(|coin| coin.amount)
+            .unwrap_or_default();
+        if sent_amount != entry.amount {
+            return Err(ContractError::InsufficientBalance { 
+                required: entry.amount.to_string(), 
+                available: sent_amount.to_string() 
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Bank sends returning `amount` of the safety deposit to whoever funded it:
+/// pro rata to its contributors when crowdfunded, otherwise to `funder`.
+/// Rounding dust goes to the last contributor so nothing stays behind.
+fn deposit_refund_msgs(escrow_info: &EscrowInfo, amount: Uint128, funder: &Addr) -> Vec<CosmosMsg> {
+    let contributors = &escrow_info.deposit_contributors;
+    let refunds = if contributors.is_empty() {
+        vec![(funder.clone(), amount)]
+    } else {
+        let total: Uint128 = contributors.iter().map(|(_, share)| *share).sum();
+        let mut remaining = amount;
+        let mut refunds = vec![];
+        for (index, (contributor, share)) in contributors.iter().enumerate() {
+            let refund = if index + 1 == contributors.len() { remaining } else { amount.multiply_ratio(*share, total) };
+            remaining -= refund;
+            refunds.push((contributor.clone(), refund));
+        }
+        refunds
+    };
+
+    refunds
+        .into_iter()
+        .filter(|(_, refund)| !refund.is_zero())
+        .map(|(recipient, refund)| CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(refund.u128(), &escrow_info.safety_deposit_denom),
+        }))
+        .collect()
+}
+
+/// Refuse to finalize an escrow with nothing left to pay out, which would
+/// otherwise hide a bookkeeping error behind a message-less response, or,
+/// with `check_solvency`, with more on the books than the contract holds.
+/// Rescues are exempt: they also sweep stray funds and are the way to close
+/// out an escrow in any state.
+fn ensure_funds_to_release(deps: Deps, env: &Env, escrow_state: &EscrowState) -> Result<(), ContractError> {
+    if escrow_state.is_drained() {
+        return Err(ContractError::OperationFailed { reason: "nothing to transfer".to_string() });
+    }
+    if CONFIG.load(deps.storage)?.check_solvency {
+        ensure_solvent(deps, env, escrow_state)?;
+    }
+    Ok(())
+}
+
+/// Compare the principal and safety deposit on the books with what the
+/// contract actually holds, so drift surfaces as `InsufficientBalance`
+/// instead of a failed transfer
+fn ensure_solvent(deps: Deps, env: &Env, escrow_state: &EscrowState) -> Result<(), ContractError> {
+    let contract = &env.contract.address;
+    let deposit_denom = &escrow_state.escrow_info.safety_deposit_denom;
+    let mut owed: Vec<(&str, Uint128)> = vec![(deposit_denom, escrow_state.native_balance)];
+
+    match &escrow_state.escrow_info.immutables.token {
+        AssetKind::Native(denom) if denom == deposit_denom => owed[0].1 += escrow_state.balance,
+        AssetKind::Native(denom) => owed.push((denom, escrow_state.balance)),
+        AssetKind::Cw20(token) => {
+            let held: BalanceResponse = deps.querier.query_wasm_smart(
+                token.to_string(),
+                &Cw20QueryMsg::Balance { address: contract.to_string() },
+            )?;
+            if held.balance < escrow_state.balance {
+                return Err(ContractError::InsufficientBalance { 
+                    required: format!("{}{}", escrow_state.balance, token), 
+                    available: format!("{}{}", held.balance, token) 
+                });
+            }
+        }
+        // Ownership of an NFT can't partially drift, its transfer fails on its own
+        AssetKind::Cw721 { .. } => {}
+    }
+
+    for (denom, amount) in owed {
+        let held = deps.querier.query_balance(contract, denom)?.amount;
+        if held < amount {
+            return Err(ContractError::InsufficientBalance { 
+                required: format!("{amount}{denom}"), 
+                available: format!("{held}{denom}") 
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Refuse to cancel once the taker has published the secret through
+/// `RevealSecret`: anyone can then claim the other leg with it, so a refund
+/// here would leave the taker with neither side
+fn ensure_secret_not_revealed(escrow_state: &EscrowState, escrow_id: u64) -> Result<(), ContractError> {
+    if escrow_state.escrow_info.revealed_secret.is_some() {
+        return Err(ContractError::SecretAlreadyRevealed { escrow_id });
+    }
+    Ok(())
+}
+
+/// A CW20 or CW721 escrow is created before its principal arrives; nothing
+/// may be withdrawn or added until the receive hook has delivered it
+fn ensure_principal_funded(escrow_state: &EscrowState, escrow_id: u64) -> Result<(), ContractError> {
+    if !escrow_state.escrow_info.principal_funded {
+        return Err(ContractError::PrincipalNotFunded { escrow_id });
+    }
+    Ok(())
+}
+
+/// Reject escrows whose timelock progression is degenerate at creation time
+fn validate_timelock_windows(
+    timelocks: &PackedTimelocks,
+    escrow_type: EscrowType,
+    current_time: u64,
+) -> Result<(), ContractError> {
+    let cancellation_stage = escrow_type.get_cancellation_stage();
+    let cancellation_time = timelocks.get_stage_time(cancellation_stage);
+    if cancellation_time <= current_time {
+        return Err(ContractError::InvalidTime { 
+            reason: format!(
+                "{cancellation_stage:?} starts at {cancellation_time}, not after current time {current_time}"
+            ) 
         });
     }
 
-    // Create immutables for escrow
-    let deployed_at = env.block.time.seconds() as u32;
-    let immutables = Immutables {
+    let final_stage = escrow_type.get_final_stage();
+    let span = timelocks.get_stage_time(final_stage) - timelocks.deployed_at() as u64;
+    if span < MIN_TIMELOCK_SPAN {
+        return Err(ContractError::InvalidTime { 
+            reason: format!(
+                "Timelock progression spans {span}s up to {final_stage:?}, minimum is {MIN_TIMELOCK_SPAN}s"
+            ) 
+        });
+    }
+
+    Ok(())
+}
+
+/// Native and safety deposit denoms requested by the escrow parameters
+fn escrow_denoms(msg: &InstantiateMsg) -> (String, String) {
+    let native_denom = msg.native_denom.clone().unwrap_or_else(|| NATIVE_DENOM.to_string());
+    let safety_deposit_denom = msg.safety_deposit_denom.clone().unwrap_or_else(|| native_denom.clone());
+    (native_denom, safety_deposit_denom)
+}
+
+/// Native coins required to create an escrow (creation fee on top).
+/// A CW20 principal is transferred to the contract separately, so native
+/// funds only cover it for native escrows; the safety deposit is always native
+/// but may use its own denom.
+fn required_native_funds(msg: &InstantiateMsg) -> Result<Vec<Coin>, ContractError> {
+    let (native_denom, safety_deposit_denom) = escrow_denoms(msg);
+    let native_principal = if msg.token.is_empty() { msg.amount } else { Uint128::zero() };
+    let native_total = native_principal
+        .checked_add(msg.creation_fee)
+        .and_then(|total| {
+            if safety_deposit_denom == native_denom {
+                total.checked_add(msg.safety_deposit)
+            } else {
+                Ok(total)
+            }
+        })
+        .map_err(|_| ContractError::InvalidAmount { 
+            amount: format!("{} + {} + {}", native_principal, msg.safety_deposit, msg.creation_fee) 
+        })?;
+
+    let mut required = vec![Coin::new(native_total.u128(), &native_denom)];
+    if safety_deposit_denom != native_denom {
+        required.push(Coin::new(msg.safety_deposit.u128(), &safety_deposit_denom));
+    }
+    Ok(required)
+}
+
+/// Build escrow immutables from creation parameters
+fn build_immutables(msg: &InstantiateMsg, deployed_at: u32, maker: Addr, taker: Addr, token: AssetKind) -> Immutables {
+    Immutables {
         order_hash: msg.order_hash.clone(),
         hashlock: msg.hashlock.clone(),
-        maker: deps.api.addr_validate(&msg.maker)?,
-        taker: deps.api.addr_validate(&msg.taker)?,
-        token: if msg.token.is_empty() {
-            Addr::unchecked("") // Native token
-        } else {
-            deps.api.addr_validate(&msg.token)?
-        },
+        maker,
+        taker,
+        token,
         amount: msg.amount,
         safety_deposit: msg.safety_deposit,
         timelocks: PackedTimelocks::new(
@@ -56,10 +258,269 @@ pub fn execute_instantiate(
             msg.timelocks.get(TimelockStage::DstPublicWithdrawal),
             msg.timelocks.get(TimelockStage::DstCancellation),
         ),
+    }
+}
+
+/// Validate a token contract address in any casing its format allows (e.g. an
+/// all-uppercase bech32 string) and return the normalized form, so the
+/// sender checks in the receive hooks compare like with like
+fn normalize_token_addr(api: &dyn Api, address: &str) -> StdResult<Addr> {
+    api.addr_humanize(&api.addr_canonicalize(address)?)
+}
+
+/// Run every creation check that doesn't depend on the attached funds and
+/// collect all failures. Creation rejects on the first one; the
+/// ValidateParams query reports them all.
+pub fn validate_escrow_params(api: &dyn Api, env: &Env, msg: &InstantiateMsg) -> Vec<ContractError> {
+    let mut errors = vec![];
+
+    let (native_denom, safety_deposit_denom) = escrow_denoms(msg);
+    errors.extend(validate_denom(&native_denom).err());
+    if safety_deposit_denom != native_denom {
+        errors.extend(validate_denom(&safety_deposit_denom).err());
+    }
+    errors.extend(required_native_funds(msg).err());
+
+    // An NFT principal is a single token of a CW721 contract
+    if let Some(token_id) = &msg.token_id {
+        if msg.token.is_empty() || token_id.is_empty() {
+            errors.push(ContractError::InvalidTokenAddress { 
+                address: format!("{}/{}", msg.token, token_id) 
+            });
+        }
+        if msg.amount != Uint128::one() {
+            errors.push(ContractError::InvalidAmount { 
+                amount: format!("NFT escrow amount must be 1, got {}", msg.amount) 
+            });
+        }
+    }
+
+    // Keep public withdrawal/cancellation incentives meaningful
+    let min_safety_deposit = msg.amount.multiply_ratio(MIN_SAFETY_DEPOSIT_BPS, 10_000u128);
+    if msg.safety_deposit < min_safety_deposit {
+        errors.push(ContractError::InvalidAmount { 
+            amount: format!(
+                "safety deposit {} is below minimum {} ({} bps of {})", 
+                msg.safety_deposit, min_safety_deposit, MIN_SAFETY_DEPOSIT_BPS, msg.amount
+            ) 
+        });
+    }
+
+    // Sanity bound on what the destination leg promises in return
+    if let Some(min_dst_amount) = msg.min_dst_amount {
+        if msg.escrow_type.is_source() && msg.dst_amount < min_dst_amount {
+            errors.push(ContractError::InvalidAmount { 
+                amount: format!("dst amount {} is below minimum {}", msg.dst_amount, min_dst_amount) 
+            });
+        }
+    }
+
+    // A crowdfunded deposit must be fully accounted for by its contributors
+    if let Some(contributors) = &msg.deposit_contributors {
+        let total = contributors.iter().map(|(_, amount)| *amount).fold(Uint128::zero(), Uint128::saturating_add);
+        if contributors.is_empty() || contributors.iter().any(|(_, amount)| amount.is_zero()) || total != msg.safety_deposit {
+            errors.push(ContractError::InvalidAmount { 
+                amount: format!(
+                    "deposit contributions total {} across {} contributors, must be non-zero shares of {}", 
+                    total, contributors.len(), msg.safety_deposit
+                ) 
+            });
+        }
+    }
+
+    if let Some(skew) = msg.timelock_skew_seconds.filter(|&skew| skew > MAX_TIMELOCK_SKEW_SECONDS) {
+        errors.push(ContractError::InvalidTime { 
+            reason: format!("Timelock skew {skew}s exceeds maximum {MAX_TIMELOCK_SKEW_SECONDS}s") 
+        });
+    }
+
+    // Addresses
+    let mut addresses = vec![&msg.maker, &msg.taker];
+    match &msg.access_token {
+        UncheckedAccessToken::Cw20(address) => addresses.push(address),
+        UncheckedAccessToken::Native(denom) => errors.extend(validate_denom(denom).err()),
+    }
+    if !msg.token.is_empty() {
+        errors.extend(normalize_token_addr(api, &msg.token).err().map(ContractError::from));
+    }
+    if msg.escrow_type.is_source() {
+        addresses.push(&msg.dst_token);
+    }
+    addresses.extend(msg.deposit_contributors.iter().flatten().map(|(contributor, _)| contributor));
+    for address in addresses {
+        errors.extend(api.addr_validate(address).err().map(ContractError::from));
+    }
+
+    // A signed order binds the hashlock and principal to what the maker agreed to
+    if let Some(order_sig) = &msg.order_sig {
+        let message_hash = order_commitment_hash(&msg.order_hash, &msg.hashlock, msg.amount, &msg.token);
+        let verified = msg.maker_pubkey.as_ref().is_some_and(|maker_pubkey| {
+            api.secp256k1_verify(&message_hash, order_sig, maker_pubkey).unwrap_or(false)
+        });
+        if !verified {
+            errors.push(ContractError::InvalidSignature {});
+        }
+    }
+
+    // Memos are stored as-is, so keep them small
+    if let Some(memo) = &msg.memo {
+        if memo.len() > MAX_MEMO_BYTES {
+            errors.push(ContractError::MemoTooLong { length: memo.len(), max: MAX_MEMO_BYTES });
+        }
+    }
+
+    // Like the other floors, the secret length can be raised but never lowered
+    // below the default, or a weak preimage could be brute-forced from the hashlock
+    if let Some(min_secret_bytes) = msg.min_secret_bytes {
+        if min_secret_bytes < DEFAULT_MIN_SECRET_BYTES {
+            errors.push(ContractError::SecretTooShort { 
+                length: min_secret_bytes as usize, 
+                min: DEFAULT_MIN_SECRET_BYTES 
+            });
+        }
+    }
+
+    // Immutables and timelocks
+    let current_time = env.block.time.seconds();
+    match u32::try_from(current_time) {
+        Ok(deployed_at) => {
+            let immutables = build_immutables(
+                msg,
+                deployed_at,
+                Addr::unchecked(&msg.maker),
+                Addr::unchecked(&msg.taker),
+                AssetKind::Native(native_denom),
+            );
+            errors.extend(immutables.validate().err().map(ContractError::from));
+            errors.extend(validate_timelock_windows(&immutables.timelocks, msg.escrow_type, current_time).err());
+        }
+        // deployed_at is packed into 32 bits, which runs out in 2106
+        Err(_) => errors.push(ContractError::InvalidCreationTime {}),
+    }
+
+    // Cancellation can't open right after creation, or a taker could fund and
+    // instantly cancel to grief the maker's matching escrow on the other chain
+    for stage in [TimelockStage::SrcCancellation, TimelockStage::DstCancellation] {
+        let offset = msg.timelocks.get(stage) as u64 * 3600;
+        if offset < MIN_CANCELLATION_OFFSET {
+            errors.push(ContractError::InvalidTimelockStage { 
+                stage: format!("{stage:?} at {offset}s is earlier than the minimum {MIN_CANCELLATION_OFFSET}s") 
+            });
+        }
+    }
+
+    // A deadline at or before the withdrawal stage would make withdrawal impossible
+    if let Some(deadline) = msg.withdrawal_deadline_offset {
+        let withdrawal_offset = msg.timelocks.get(msg.escrow_type.get_withdrawal_stage());
+        if deadline <= withdrawal_offset {
+            errors.push(ContractError::InvalidTime { 
+                reason: format!(
+                    "Withdrawal deadline {}h must be after the withdrawal stage at {}h", 
+                    deadline, withdrawal_offset
+                ) 
+            });
+        }
+    }
+
+    // Validate the rescue delay this escrow will use, default or override
+    let rescue_delay = msg.rescue_delay_override.unwrap_or(msg.rescue_delay);
+    if rescue_delay < MIN_RESCUE_DELAY {
+        errors.push(ContractError::InvalidTime { 
+            reason: format!("Rescue delay {rescue_delay} is below minimum {MIN_RESCUE_DELAY}") 
+        });
+    }
+
+    // Admin rescue is a last resort, only after the taker's own rescue window
+    if msg.admin_rescue_delay <= rescue_delay {
+        errors.push(ContractError::InvalidTime { 
+            reason: format!(
+                "Admin rescue delay {} must exceed rescue delay {}", 
+                msg.admin_rescue_delay, rescue_delay
+            ) 
+        });
+    }
+
+    // Bound how long funds can stay locked; stage offsets are in hours
+    let final_stage = msg.escrow_type.get_final_stage();
+    let lifetime = (u64::from(msg.timelocks.get(final_stage)) * 3600).saturating_add(rescue_delay);
+    if lifetime > MAX_LIFETIME_SECONDS {
+        errors.push(ContractError::InvalidTime { 
+            reason: format!(
+                "Lifetime {lifetime}s up to {final_stage:?} plus rescue delay exceeds maximum {MAX_LIFETIME_SECONDS}s"
+            ) 
+        });
+    }
+
+    errors
+}
+
+pub fn execute_instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    // Validate escrow parameters (same checks as the ValidateParams query)
+    if let Some(err) = validate_escrow_params(deps.api, &env, &msg).into_iter().next() {
+        return Err(err);
+    }
+
+    // Validate that the correct amount of funds was sent
+    validate_native_funds(&info.funds, &required_native_funds(&msg)?)?;
+
+    // Save contract configuration
+    let (native_denom, safety_deposit_denom) = escrow_denoms(&msg);
+    let config = Config {
+        owner: info.sender.clone(),
+        access_token: match &msg.access_token {
+            UncheckedAccessToken::Cw20(address) => AccessToken::Cw20(deps.api.addr_validate(address)?),
+            UncheckedAccessToken::Native(denom) => AccessToken::Native(denom.clone()),
+        },
+        rescue_delay: msg.rescue_delay,
+        factory: info.sender.clone(),
+        creation_fee: msg.creation_fee,
+        public_caller_reward: msg.public_caller_reward,
+        native_denom,
+        admin_rescue_delay: msg.admin_rescue_delay,
+        rescue_denom_allowlist: None,
+        min_secret_bytes: msg.min_secret_bytes.unwrap_or(DEFAULT_MIN_SECRET_BYTES),
+        public_authority_pubkey: msg.public_authority_pubkey.clone(),
+        withdraw_fee: msg.withdraw_fee,
+        treasury: match &msg.treasury {
+            Some(treasury) => deps.api.addr_validate(treasury)?,
+            None => info.sender.clone(),
+        },
+        default_access_token_threshold: msg.default_access_token_threshold,
+        check_solvency: msg.check_solvency,
+        event_prefix: msg.event_prefix.clone().unwrap_or_default(),
+        max_timelock_extension_hours: msg.max_timelock_extension_hours.unwrap_or_default(),
+        timelock_skew_seconds: msg.timelock_skew_seconds.unwrap_or_default(),
     };
+    CONFIG.save(deps.storage, &config)?;
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    COLLECTED_FEES.save(deps.storage, &msg.creation_fee)?;
+    WITHDRAW_FEES_PAID.save(deps.storage, &Uint128::zero())?;
 
-    // Validate immutables
-    immutables.validate()?;
+    // Create immutables for escrow
+    let deployed_at = u32::try_from(env.block.time.seconds())
+        .map_err(|_| ContractError::InvalidCreationTime {})?;
+    // Token principals are only credited once their receive hook delivers them
+    let principal_funded = msg.token.is_empty();
+    let token = match (msg.token.is_empty(), &msg.token_id) {
+        (true, _) => AssetKind::Native(config.native_denom.clone()),
+        (false, None) => AssetKind::Cw20(normalize_token_addr(deps.api, &msg.token)?),
+        (false, Some(token_id)) => AssetKind::Cw721 {
+            contract: normalize_token_addr(deps.api, &msg.token)?,
+            token_id: token_id.clone(),
+        },
+    };
+    let immutables = build_immutables(
+        &msg,
+        deployed_at,
+        deps.api.addr_validate(&msg.maker)?,
+        deps.api.addr_validate(&msg.taker)?,
+        token,
+    );
 
     // Get next escrow ID
     let escrow_id = get_next_escrow_id(deps.storage)?;
@@ -81,367 +542,1527 @@ pub fn execute_instantiate(
         immutables,
         dst_complement,
         escrow_type: msg.escrow_type,
-        is_active: true,
+        status: EscrowStatus::Active,
         created_at: env.block.time,
+        created_at_height: env.block.height,
+        completed_at_height: None,
+        rescue_delay: msg.rescue_delay_override,
+        ibc_memo: msg.ibc_memo,
+        safety_deposit_denom,
+        maker_pubkey: msg.maker_pubkey,
+        taker_pubkey: msg.taker_pubkey,
+        swap_id: compute_swap_id(&msg.order_hash, &msg.hashlock),
+        withdrawal_deadline: msg.withdrawal_deadline_offset,
+        access_token_threshold: msg.access_token_threshold,
+        memo: msg.memo.clone(),
+        deposit_contributors: msg.deposit_contributors.unwrap_or_default()
+            .into_iter()
+            .map(|(contributor, amount)| Ok((deps.api.addr_validate(&contributor)?, amount)))
+            .collect::<StdResult<_>>()?,
+        revealed_secret: None,
+        creator: info.sender.clone(),
+        timelock_extension_hours: 0,
+        principal_funded,
     };
 
     let escrow_state = EscrowState {
         escrow_info,
-        balance: msg.amount,
+        balance: if principal_funded { msg.amount } else { Uint128::zero() },
         native_balance: msg.safety_deposit,
     };
 
-    // Save escrow
-    ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
-
-    Ok(Response::new()
+    // Save escrow and index it by order hash (an order can fan out into several escrows)
+    create_escrow(deps.storage, escrow_id, &escrow_state)?;
+    lock_value(deps.storage, &escrow_state)?;
+    ORDER_HASH_ESCROWS.update(deps.storage, &msg.order_hash, |ids| -> StdResult<_> {
+        let mut ids = ids.unwrap_or_default();
+        ids.push(escrow_id);
+        Ok(ids)
+    })?;
+    SWAP_ID_ESCROWS.save(deps.storage, &escrow_state.escrow_info.swap_id, &escrow_id)?;
+    MAKER_ESCROWS.save(deps.storage, (&escrow_state.escrow_info.immutables.maker, escrow_id), &())?;
+    TAKER_ESCROWS.save(deps.storage, (&escrow_state.escrow_info.immutables.taker, escrow_id), &())?;
+
+    let mut response = Response::new()
         .add_attribute("method", "instantiate")
         .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("order_hash", msg.order_hash)
+        .add_attribute("hashlock", msg.hashlock)
+        .add_attribute("swap_id", escrow_state.escrow_info.swap_id.clone())
         .add_attribute("escrow_type", format!("{:?}", msg.escrow_type))
         .add_attribute("amount", msg.amount.to_string())
-        .add_attribute("safety_deposit", msg.safety_deposit.to_string()))
+        .add_attribute("safety_deposit", msg.safety_deposit.to_string())
+        .add_attribute("creation_fee", msg.creation_fee.to_string());
+
+    if let Some(memo) = msg.memo {
+        response = response.add_attribute("memo", memo);
+    }
+
+    // Relayers build the matching destination escrow from these attributes alone
+    if let Some(dst_complement) = &escrow_state.escrow_info.dst_complement {
+        response = response
+            .add_attribute("dst_chain_id", dst_complement.chain_id.clone())
+            .add_attribute("dst_token", dst_complement.token.to_string())
+            .add_attribute("dst_amount", dst_complement.amount.to_string())
+            .add_attribute("dst_safety_deposit", dst_complement.safety_deposit.to_string());
+    }
+
+    Ok(response)
 }
 
-/// Source-specific withdraw function
-pub fn execute_withdraw_src(
+/// CW20 receive hook: the token contract calls this after a `Send` to the escrow
+pub fn execute_receive(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
-    escrow_id: u64,
-    secret: String,
+    wrapper: Cw20ReceiveMsg,
 ) -> Result<Response, ContractError> {
-    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
-        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+    let hook: Cw20HookMsg = from_json(&wrapper.msg)?;
+    let (Cw20HookMsg::DeployEscrow { escrow_id } | Cw20HookMsg::TopUp { escrow_id }) = hook;
 
-    // Validate escrow type
-    if !escrow_state.escrow_info.escrow_type.is_source() {
-        return Err(ContractError::InvalidImmutables { 
-            reason: "This operation is only valid for source escrows".to_string() 
+    // Nothing credits CW20 sent to a native escrow, so revert the transfer
+    // instead of stranding the tokens
+    let escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+    if let AssetKind::Native(denom) = &escrow_state.escrow_info.immutables.token {
+        return Err(ContractError::InvalidTokenAddress { 
+            address: format!("{} sent to escrow {escrow_id} holding native {denom}", info.sender) 
         });
     }
 
-    // Access control: only taker can withdraw
-    if info.sender != escrow_state.escrow_info.immutables.taker {
-        return Err(ContractError::OnlyTaker {});
+    match hook {
+        Cw20HookMsg::DeployEscrow { escrow_id } => {
+            execute_deploy_escrow_with_cw20_send(deps, info, wrapper, escrow_id)
+        }
+        Cw20HookMsg::TopUp { escrow_id } => {
+            let depositor = deps.api.addr_validate(&wrapper.sender)?;
+            let asset = AssetKind::Cw20(info.sender);
+            top_up(deps, env, escrow_id, &depositor, asset, wrapper.amount)
+        }
+    }
+}
+
+/// Deliver the CW20 principal of an escrow atomically through `Send`.
+/// The escrow itself (and its native safety deposit) is created at instantiation,
+/// the principal only counts once it arrives here.
+fn execute_deploy_escrow_with_cw20_send(
+    deps: DepsMut,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+    let immutables = escrow_state.escrow_info.immutables.clone();
+
+    // Only the escrow's own CW20 token contract may deliver the principal
+    if !matches!(&immutables.token, AssetKind::Cw20(token) if *token == info.sender) {
+        return Err(ContractError::InvalidTokenAddress { address: info.sender.to_string() });
     }
 
     // State validation
-    if !escrow_state.escrow_info.is_active {
-        return Err(ContractError::EscrowNotActive { escrow_id });
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
     }
 
-    let immutables = &escrow_state.escrow_info.immutables;
-    
-    // Secret validation
-    let secret_hash = Sha256::digest(secret.as_bytes());
-    let secret_hash_hex = format!("{secret_hash:x}");
-    
-    if secret_hash_hex != immutables.hashlock {
-        return Err(ContractError::InvalidSecret {});
+    if escrow_state.escrow_info.principal_funded {
+        return Err(ContractError::InvalidAmount { 
+            amount: format!("principal already funded with {}", immutables.amount) 
+        });
     }
 
-    // Timelock validation: allow in both PRIVATE and PUBLIC withdrawal stages
-    let current_time = env.block.time.seconds();
-    let private_stage = TimelockStage::SrcWithdrawal;
-    let public_stage = TimelockStage::SrcPublicWithdrawal;
-    let in_private = immutables.timelocks.is_within_stage(current_time, private_stage);
-    let in_public = immutables.timelocks.is_within_stage(current_time, public_stage);
-    if !(in_private || in_public) {
-        return Err(ContractError::TimelockNotExpired { 
-            stage: "SrcWithdrawal or SrcPublicWithdrawal".to_string() 
+    if wrapper.amount != immutables.amount {
+        return Err(ContractError::InsufficientBalance { 
+            required: immutables.amount.to_string(), 
+            available: wrapper.amount.to_string() 
         });
     }
 
-    // Transfer tokens to taker (source behavior)
-    let mut messages: Vec<CosmosMsg> = vec![];
+    escrow_state.balance = wrapper.amount;
+    escrow_state.escrow_info.principal_funded = true;
+    ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    add_locked_value(deps.storage, immutables.token.clone(), wrapper.amount)?;
+    record_principal_volume(deps.storage, immutables.token, wrapper.amount)?;
 
-    if escrow_state.balance > Uint128::zero() {
-        if immutables.token == Addr::unchecked("") {
-            messages.push(CosmosMsg::Bank(BankMsg::Send {
-                to_address: immutables.taker.to_string(),
-                amount: coins(escrow_state.balance.u128(), "uatom"),
-            }));
-        } else {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: immutables.token.to_string(),
-                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                    recipient: immutables.taker.to_string(),
-                    amount: escrow_state.balance,
-                })?,
-                funds: vec![],
-            }));
+    Ok(Response::new()
+        .add_attribute("method", "deploy_escrow_with_cw20_send")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("order_hash", immutables.order_hash)
+        .add_attribute("sender", wrapper.sender)
+        .add_attribute("amount", wrapper.amount.to_string()))
+}
+
+/// CW721 receive hook: the NFT contract calls this after a `SendNft` to the escrow
+pub fn execute_receive_nft(
+    deps: DepsMut,
+    info: MessageInfo,
+    wrapper: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    match from_json(&wrapper.msg)? {
+        Cw20HookMsg::DeployEscrow { escrow_id } => {
+            execute_deploy_escrow_with_nft(deps, info, wrapper, escrow_id)
         }
+        // A single NFT has nothing to add to
+        Cw20HookMsg::TopUp { .. } => Err(ContractError::InvalidAmount { 
+            amount: format!("NFT {} can't be topped up", wrapper.token_id) 
+        }),
     }
+}
 
-    // Transfer safety deposit to caller
-    if escrow_state.native_balance > Uint128::zero() {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: coins(escrow_state.native_balance.u128(), "uatom"),
-        }));
+/// Deliver the NFT principal of an escrow through `SendNft`. Ownership moves
+/// to the contract before the hook runs, so the same NFT can't fund twice.
+fn execute_deploy_escrow_with_nft(
+    deps: DepsMut,
+    info: MessageInfo,
+    wrapper: Cw721ReceiveMsg,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+    let immutables = escrow_state.escrow_info.immutables.clone();
+
+    // Only the escrowed NFT itself may be delivered
+    match &immutables.token {
+        AssetKind::Cw721 { contract, token_id } if *contract == info.sender && *token_id == wrapper.token_id => {}
+        _ => return Err(ContractError::InvalidTokenAddress { 
+            address: format!("{}/{}", info.sender, wrapper.token_id) 
+        }),
     }
 
-    // Mark escrow as inactive
-    escrow_state.escrow_info.is_active = false;
+    // State validation
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
+    }
+
+    escrow_state.balance = immutables.amount;
+    escrow_state.escrow_info.principal_funded = true;
     ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    add_locked_value(deps.storage, immutables.token.clone(), immutables.amount)?;
+    record_principal_volume(deps.storage, immutables.token, immutables.amount)?;
 
     Ok(Response::new()
-        .add_messages(messages)
-        .add_attribute("method", "withdraw_src")
+        .add_attribute("method", "deploy_escrow_with_nft")
         .add_attribute("escrow_id", escrow_id.to_string())
-        .add_attribute("recipient", immutables.taker.to_string())
-        .add_attribute("secret", secret))
+        .add_attribute("order_hash", immutables.order_hash)
+        .add_attribute("sender", wrapper.sender)
+        .add_attribute("token_id", wrapper.token_id))
 }
 
-/// Destination-specific withdraw function
-pub fn execute_withdraw_dst(
+/// Add native funds to the principal of an active escrow
+pub fn execute_top_up(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     escrow_id: u64,
-    secret: String,
+) -> Result<Response, ContractError> {
+    let amount = match info.funds.as_slice() {
+        [coin] if !coin.amount.is_zero() => coin.amount,
+        _ => {
+            let sent = info.funds.iter().map(|coin| coin.to_string()).collect::<Vec<_>>().join(",");
+            return Err(ContractError::InvalidAmount { amount: sent });
+        }
+    };
+    let asset = AssetKind::Native(info.funds[0].denom.clone());
+    top_up(deps, env, escrow_id, &info.sender, asset, amount)
+}
+
+/// Grow the principal by `amount` of `asset`. Only whoever funded the
+/// principal (the party a cancellation refunds) may add to it, and only
+/// until cancellation opens. The hashlock and immutables are unchanged.
+fn top_up(
+    deps: DepsMut,
+    env: Env,
+    escrow_id: u64,
+    depositor: &Addr,
+    asset: AssetKind,
+    amount: Uint128,
 ) -> Result<Response, ContractError> {
     let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
         .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+    let escrow_info = &escrow_state.escrow_info;
+    let immutables = &escrow_info.immutables;
 
-    // Validate escrow type
-    if !escrow_state.escrow_info.escrow_type.is_destination() {
-        return Err(ContractError::InvalidImmutables { 
-            reason: "This operation is only valid for destination escrows".to_string() 
-        });
+    // Only more of the escrowed asset, and never a second NFT
+    if asset != immutables.token || matches!(asset, AssetKind::Cw721 { .. }) {
+        return Err(ContractError::InvalidTokenAddress { address: asset.as_str().to_string() });
     }
 
-    // Access control: only taker can withdraw
-    if info.sender != escrow_state.escrow_info.immutables.taker {
-        return Err(ContractError::OnlyTaker {});
+    // Access control: the depositor is the one a cancellation refunds
+    let escrow_type = escrow_info.escrow_type;
+    if *depositor != escrow_type.get_cancellation_recipient(&immutables.maker, &immutables.taker) {
+        return Err(ContractError::Unauthorized { 
+            reason: "Only the depositor can top up the escrow".to_string() 
+        });
     }
 
     // State validation
-    if !escrow_state.escrow_info.is_active {
-        return Err(ContractError::EscrowNotActive { escrow_id });
+    if !escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_info.status 
+        });
     }
 
-    let immutables = &escrow_state.escrow_info.immutables;
-    
-    // Secret validation
-    let secret_hash = Sha256::digest(secret.as_bytes());
-    let secret_hash_hex = format!("{secret_hash:x}");
-    
-    if secret_hash_hex != immutables.hashlock {
-        return Err(ContractError::InvalidSecret {});
+    ensure_principal_funded(&escrow_state, escrow_id)?;
+
+    // Timelock validation: the principal is fixed once cancellation opens
+    let stage = escrow_type.get_cancellation_stage();
+    if immutables.timelocks.is_within_stage(env.block.time.seconds(), stage) {
+        return Err(ContractError::TimelockExpired { 
+            stage: format!("{stage:?}") 
+        });
+    }
+
+    // The existing safety deposit must still cover the larger principal
+    let balance = escrow_state.balance.checked_add(amount)
+        .map_err(|_| ContractError::InvalidAmount { 
+            amount: format!("{} + {}", escrow_state.balance, amount) 
+        })?;
+    let min_safety_deposit = balance.multiply_ratio(MIN_SAFETY_DEPOSIT_BPS, 10_000u128);
+    if immutables.safety_deposit < min_safety_deposit {
+        return Err(ContractError::InvalidAmount { 
+            amount: format!(
+                "safety deposit {} is below minimum {} ({} bps of {})", 
+                immutables.safety_deposit, min_safety_deposit, MIN_SAFETY_DEPOSIT_BPS, balance
+            ) 
+        });
+    }
+
+    escrow_state.balance = balance;
+    ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    add_locked_value(deps.storage, asset.clone(), amount)?;
+    record_principal_volume(deps.storage, asset, amount)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "top_up")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("balance", balance.to_string()))
+}
+
+/// Message releasing the escrow principal to `recipient`
+fn principal_transfer_msg(token: &AssetKind, recipient: &str, amount: Uint128) -> StdResult<CosmosMsg> {
+    let msg = match token {
+        AssetKind::Native(denom) => CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), denom),
+        }),
+        AssetKind::Cw20(token) => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: token.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }),
+        AssetKind::Cw721 { contract, token_id } => CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract.to_string(),
+            msg: to_json_binary(&Cw721ExecuteMsg::TransferNft {
+                recipient: recipient.to_string(),
+                token_id: token_id.clone(),
+            })?,
+            funds: vec![],
+        }),
+    };
+    Ok(msg)
+}
+
+/// Reject secrets shorter than the configured minimum, even if their hash matches
+fn validate_secret_length(deps: Deps, secret: &str) -> Result<(), ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if secret.len() < config.min_secret_bytes as usize {
+        return Err(ContractError::SecretTooShort { 
+            length: secret.len(), 
+            min: config.min_secret_bytes 
+        });
+    }
+    Ok(())
+}
+
+/// Give a slow swap more time by pushing back the source cancellation stages.
+/// The withdrawal stages stay where they are, so only the windows for
+/// withdrawing by secret grow.
+pub fn execute_extend_timelocks(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    additional_hours: u8,
+) -> Result<Response, ContractError> {
+    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+
+    // Validate escrow type
+    if !escrow_state.escrow_info.escrow_type.is_source() {
+        return Err(ContractError::WrongEscrowType { 
+            expected: EscrowType::Source, 
+            found: escrow_state.escrow_info.escrow_type, 
+        });
+    }
+
+    // Access control: only maker can extend
+    if info.sender != escrow_state.escrow_info.immutables.maker {
+        return Err(ContractError::OnlyMaker {});
+    }
+
+    // State validation
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
+    }
+
+    // Timelock validation: a refund that's already claimable can't be deferred
+    let timelocks = &escrow_state.escrow_info.immutables.timelocks;
+    if timelocks.is_within_stage(env.block.time.seconds(), TimelockStage::SrcCancellation) {
+        return Err(ContractError::TimelockExpired { 
+            stage: format!("{:?}", TimelockStage::SrcCancellation) 
+        });
+    }
+
+    // The configured cap bounds all extensions of this escrow together
+    let config = CONFIG.load(deps.storage)?;
+    let total_hours = escrow_state.escrow_info.timelock_extension_hours.saturating_add(additional_hours);
+    if additional_hours == 0 || total_hours > config.max_timelock_extension_hours {
+        return Err(ContractError::InvalidTime { 
+            reason: format!(
+                "Extension of {additional_hours}h brings the total to {total_hours}h, must be non-zero and at most {}h",
+                config.max_timelock_extension_hours
+            ) 
+        });
+    }
+
+    let extended = timelocks.extend_src_cancellation(additional_hours)
+        .ok_or_else(|| ContractError::InvalidTime { 
+            reason: format!("Extension of {additional_hours}h overflows the {}h stage offset limit", u8::MAX) 
+        })?;
+    extended.validate()?;
+
+    // Same lifetime bound as at creation
+    let rescue_delay = escrow_state.escrow_info.effective_rescue_delay(config.rescue_delay);
+    let final_stage = TimelockStage::SrcPublicCancellation;
+    let lifetime = (u64::from(extended.get(final_stage)) * 3600).saturating_add(rescue_delay);
+    if lifetime > MAX_LIFETIME_SECONDS {
+        return Err(ContractError::InvalidTime { 
+            reason: format!(
+                "Lifetime {lifetime}s up to {final_stage:?} plus rescue delay exceeds maximum {MAX_LIFETIME_SECONDS}s"
+            ) 
+        });
+    }
+
+    let cancellation_at = extended.get_stage_time(TimelockStage::SrcCancellation);
+    escrow_state.escrow_info.immutables.timelocks = extended;
+    escrow_state.escrow_info.timelock_extension_hours = total_hours;
+    ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "extend_timelocks")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("order_hash", escrow_state.escrow_info.immutables.order_hash)
+        .add_attribute("additional_hours", additional_hours.to_string())
+        .add_attribute("cancellation_at", cancellation_at.to_string()))
+}
+
+/// Publish the secret on the source chain ahead of withdrawing, e.g. when the
+/// destination leg must settle first. Moves no funds and leaves the escrow
+/// active, but from then on it can only be withdrawn (or rescued), never cancelled.
+pub fn execute_reveal_secret(
+    deps: DepsMut,
+    info: MessageInfo,
+    escrow_id: u64,
+    secret: String,
+) -> Result<Response, ContractError> {
+    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+
+    // Validate escrow type
+    if !escrow_state.escrow_info.escrow_type.is_source() {
+        return Err(ContractError::WrongEscrowType { 
+            expected: EscrowType::Source, 
+            found: escrow_state.escrow_info.escrow_type, 
+        });
+    }
+
+    // Access control: only taker can reveal
+    if info.sender != escrow_state.escrow_info.immutables.taker {
+        return Err(ContractError::OnlyTaker {});
+    }
+
+    // State validation
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
+    }
+
+    // Secret validation
+    validate_secret_length(deps.as_ref(), &secret)?;
+    if compute_hashlock(&secret) != escrow_state.escrow_info.immutables.hashlock {
+        return Err(ContractError::InvalidSecret {});
+    }
+
+    escrow_state.escrow_info.revealed_secret = Some(secret.clone());
+    ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "secret_revealed")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("order_hash", escrow_state.escrow_info.immutables.order_hash)
+        .add_attribute("secret", secret))
+}
+
+/// Source-specific withdraw function
+pub fn execute_withdraw_src(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    secret: String,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+
+    // Validate escrow type
+    if !escrow_state.escrow_info.escrow_type.is_source() {
+        return Err(ContractError::WrongEscrowType { 
+            expected: EscrowType::Source, 
+            found: escrow_state.escrow_info.escrow_type, 
+        });
+    }
+
+    // Access control: only taker can withdraw
+    if info.sender != escrow_state.escrow_info.immutables.taker {
+        return Err(ContractError::OnlyTaker {});
+    }
+
+    // State validation
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
+    }
+    ensure_funds_to_release(deps.as_ref(), &env, &escrow_state)?;
+    ensure_principal_funded(&escrow_state, escrow_id)?;
+
+    let immutables = escrow_state.escrow_info.immutables.clone();
+
+    // Resolve the payout address before anything is debited, so a bad one
+    // fails the call without touching state
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => immutables.taker.clone(),
+    };
+    
+    // Secret validation
+    validate_secret_length(deps.as_ref(), &secret)?;
+    if compute_hashlock(&secret) != immutables.hashlock {
+        return Err(ContractError::InvalidSecret {});
+    }
+
+    // Timelock validation: allow in both PRIVATE and PUBLIC withdrawal stages
+    let current_time = env.block.time.seconds();
+    let private_stage = TimelockStage::SrcWithdrawal;
+    let public_stage = TimelockStage::SrcPublicWithdrawal;
+    let in_private = immutables.timelocks.is_within_stage(current_time, private_stage);
+    let in_public = immutables.timelocks.is_within_stage(current_time, public_stage);
+    if !(in_private || in_public) {
+        return Err(ContractError::TimelockNotExpired { 
+            stage: "SrcWithdrawal or SrcPublicWithdrawal".to_string(), 
+            required_at: immutables.timelocks.get_stage_time(private_stage), 
+            current: current_time, 
+        });
+    }
+
+    // Withdrawal windows close once cancellation opens, give or take the configured skew
+    let skew = CONFIG.load(deps.storage)?.timelock_skew_seconds;
+    if !(immutables.timelocks.is_within_window_with_grace(current_time, private_stage, skew)
+        || immutables.timelocks.is_within_window_with_grace(current_time, public_stage, skew))
+    {
+        return Err(ContractError::TimelockExpired { 
+            stage: format!("{public_stage:?}") 
+        });
+    }
+
+    // A configured deadline closes withdrawals early, even mid-window
+    if escrow_state.escrow_info.is_past_withdrawal_deadline(current_time) {
+        return Err(ContractError::TimelockExpired { 
+            stage: "Withdrawal deadline".to_string() 
+        });
+    }
+
+    // Transfer tokens to taker (source behavior) or the payout address it chose
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    if escrow_state.balance > Uint128::zero() {
+        messages.push(principal_transfer_msg(&immutables.token, recipient.as_str(), escrow_state.balance)?);
+    }
+
+    // Withdraw fee goes to the treasury, the rest of the safety deposit to the caller
+    let withdraw_fee = take_withdraw_fee(deps.storage, &mut escrow_state, &mut messages)?;
+    if escrow_state.native_balance > Uint128::zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(escrow_state.native_balance.u128(), &escrow_state.escrow_info.safety_deposit_denom),
+        }));
+    }
+
+    // Mark escrow as withdrawn
+    escrow_state.escrow_info.status = EscrowStatus::Withdrawn;
+    escrow_state.escrow_info.completed_at_height = Some(env.block.height);
+    ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    release_value(deps.storage, &escrow_state)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "withdraw_src")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("order_hash", immutables.order_hash)
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("withdraw_fee", withdraw_fee.to_string())
+        .add_attribute("secret", secret))
+}
+
+/// Destination-specific withdraw function
+pub fn execute_withdraw_dst(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    secret: String,
+) -> Result<Response, ContractError> {
+    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+
+    // Validate escrow type
+    if !escrow_state.escrow_info.escrow_type.is_destination() {
+        return Err(ContractError::WrongEscrowType { 
+            expected: EscrowType::Destination, 
+            found: escrow_state.escrow_info.escrow_type, 
+        });
+    }
+
+    // Access control: only taker can withdraw
+    if info.sender != escrow_state.escrow_info.immutables.taker {
+        return Err(ContractError::OnlyTaker {});
+    }
+
+    // State validation
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
+    }
+    ensure_funds_to_release(deps.as_ref(), &env, &escrow_state)?;
+    ensure_principal_funded(&escrow_state, escrow_id)?;
+
+    let immutables = escrow_state.escrow_info.immutables.clone();
+    
+    // Secret validation
+    validate_secret_length(deps.as_ref(), &secret)?;
+    if compute_hashlock(&secret) != immutables.hashlock {
+        return Err(ContractError::InvalidSecret {});
+    }
+
+    // Timelock validation
+    let current_time = env.block.time.seconds();
+    let stage = escrow_state.escrow_info.escrow_type.get_withdrawal_stage();
+    let public_stage = escrow_state.escrow_info.escrow_type.get_public_withdrawal_stage();
+
+    if !immutables.timelocks.is_within_stage(current_time, stage) {
+        return Err(ContractError::TimelockNotExpired { 
+            stage: format!("{stage:?}"), 
+            required_at: immutables.timelocks.get_stage_time(stage), 
+            current: current_time, 
+        });
+    }
+
+    // Withdrawal windows close once cancellation opens, give or take the configured skew
+    let skew = CONFIG.load(deps.storage)?.timelock_skew_seconds;
+    if !(immutables.timelocks.is_within_window_with_grace(current_time, stage, skew)
+        || immutables.timelocks.is_within_window_with_grace(current_time, public_stage, skew))
+    {
+        return Err(ContractError::TimelockExpired { 
+            stage: format!("{stage:?}") 
+        });
+    }
+
+    // A configured deadline closes withdrawals early, even mid-window
+    if escrow_state.escrow_info.is_past_withdrawal_deadline(current_time) {
+        return Err(ContractError::TimelockExpired { 
+            stage: "Withdrawal deadline".to_string() 
+        });
+    }
+
+    // Transfer tokens to maker (destination behavior)
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    if escrow_state.balance > Uint128::zero() {
+        messages.push(principal_transfer_msg(&immutables.token, immutables.maker.as_str(), escrow_state.balance)?);
+    }
+
+    // Withdraw fee goes to the treasury, the rest of the safety deposit to the caller
+    let withdraw_fee = take_withdraw_fee(deps.storage, &mut escrow_state, &mut messages)?;
+    if escrow_state.native_balance > Uint128::zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(escrow_state.native_balance.u128(), &escrow_state.escrow_info.safety_deposit_denom),
+        }));
+    }
+
+    // Mark escrow as withdrawn
+    escrow_state.escrow_info.status = EscrowStatus::Withdrawn;
+    escrow_state.escrow_info.completed_at_height = Some(env.block.height);
+    ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    release_value(deps.storage, &escrow_state)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "withdraw_dst")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("order_hash", immutables.order_hash)
+        .add_attribute("recipient", immutables.maker.to_string())
+        .add_attribute("withdraw_fee", withdraw_fee.to_string())
+        .add_attribute("secret", secret))
+}
+
+/// Move the configured withdraw fee out of the safety deposit and queue it
+/// for the treasury. The fee is capped at the deposit, so a small deposit is
+/// taken in full rather than failing the withdrawal.
+fn take_withdraw_fee(
+    storage: &mut dyn Storage,
+    escrow_state: &mut EscrowState,
+    messages: &mut Vec<CosmosMsg>,
+) -> Result<Uint128, ContractError> {
+    let config = CONFIG.load(storage)?;
+    let fee = escrow_state.native_balance.min(config.withdraw_fee);
+    if fee.is_zero() {
+        return Ok(fee);
+    }
+
+    escrow_state.debit_deposit(fee)?;
+    WITHDRAW_FEES_PAID.update(storage, |paid| -> StdResult<_> {
+        Ok(paid.checked_add(fee)?)
+    })?;
+    messages.push(CosmosMsg::Bank(BankMsg::Send {
+        to_address: config.treasury.to_string(),
+        amount: coins(fee.u128(), &escrow_state.escrow_info.safety_deposit_denom),
+    }));
+    Ok(fee)
+}
+
+/// Source-specific cancel function
+pub fn execute_cancel_src(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+
+    // Validate escrow type
+    if !escrow_state.escrow_info.escrow_type.is_source() {
+        return Err(ContractError::WrongEscrowType { 
+            expected: EscrowType::Source, 
+            found: escrow_state.escrow_info.escrow_type, 
+        });
+    }
+
+    // Access control: source cancellation authorization matrix
+    //
+    // | caller | SrcCancellation (private) | SrcPublicCancellation onwards |
+    // |--------|---------------------------|-------------------------------|
+    // | taker  | allowed                   | allowed                       |
+    // | maker  | rejected (OnlyTaker)      | allowed                       |
+    // | other  | rejected                  | rejected (use PublicCancelSrc)|
+    //
+    // The maker is the refund recipient, so once the private window has passed
+    // it can reclaim its funds without depending on an absent taker.
+    let is_taker = info.sender == escrow_state.escrow_info.immutables.taker;
+    let is_maker = info.sender == escrow_state.escrow_info.immutables.maker;
+    if !is_taker && !is_maker {
+        return Err(ContractError::OnlyTaker {});
+    }
+
+    // State validation
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
+    }
+    ensure_funds_to_release(deps.as_ref(), &env, &escrow_state)?;
+    ensure_secret_not_revealed(&escrow_state, escrow_id)?;
+
+    let immutables = &escrow_state.escrow_info.immutables;
+    
+    // Timelock validation
+    let current_time = env.block.time.seconds();
+    let stage = escrow_state.escrow_info.escrow_type.get_cancellation_stage();
+
+    if !immutables.timelocks.is_within_stage(current_time, stage) {
+        return Err(ContractError::TimelockNotExpired { 
+            stage: format!("{stage:?}"), 
+            required_at: immutables.timelocks.get_stage_time(stage), 
+            current: current_time, 
+        });
+    }
+
+    // Private cancellation window is reserved for the taker
+    if !is_taker && !immutables.timelocks.is_within_stage(current_time, TimelockStage::SrcPublicCancellation) {
+        return Err(ContractError::OnlyTaker {});
+    }
+
+    // Transfer tokens to maker (source behavior)
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    if escrow_state.balance > Uint128::zero() {
+        messages.push(principal_transfer_msg(&immutables.token, immutables.maker.as_str(), escrow_state.balance)?);
+    }
+
+    // Return the safety deposit to the creator or contributors who posted it;
+    // only a public cancellation pays it to whoever submits
+    messages.extend(deposit_refund_msgs(&escrow_state.escrow_info, escrow_state.native_balance, &escrow_state.escrow_info.creator));
+
+    // Mark escrow as cancelled
+    escrow_state.escrow_info.status = EscrowStatus::Cancelled;
+    escrow_state.escrow_info.completed_at_height = Some(env.block.height);
+    ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    release_value(deps.storage, &escrow_state)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "cancel_src")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("order_hash", immutables.order_hash.clone())
+        .add_attribute("recipient", immutables.maker.to_string()))
+}
+
+/// Source cancellation authorized by the maker's secp256k1 signature and
+/// submitted by a relayer, for makers who can't transact on this chain
+pub fn execute_cancel_src_signed(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    maker_sig: Binary,
+    maker_pubkey: Binary,
+) -> Result<Response, ContractError> {
+    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+
+    // Validate escrow type
+    if !escrow_state.escrow_info.escrow_type.is_source() {
+        return Err(ContractError::WrongEscrowType { 
+            expected: EscrowType::Source, 
+            found: escrow_state.escrow_info.escrow_type, 
+        });
+    }
+
+    // State validation
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
+    }
+    ensure_funds_to_release(deps.as_ref(), &env, &escrow_state)?;
+    ensure_secret_not_revealed(&escrow_state, escrow_id)?;
+
+    let immutables = &escrow_state.escrow_info.immutables;
+
+    // Signature validation: the key must be the one the maker registered at creation
+    if escrow_state.escrow_info.maker_pubkey.as_ref() != Some(&maker_pubkey) {
+        return Err(ContractError::InvalidSignature {});
+    }
+    let message_hash = cancel_authorization_hash(&env.contract.address, escrow_id, &immutables.order_hash);
+    let verified = deps.api
+        .secp256k1_verify(&message_hash, &maker_sig, &maker_pubkey)
+        .map_err(|_| ContractError::InvalidSignature {})?;
+    if !verified {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    // Timelock validation: the signature stands in for the maker, who may only
+    // cancel once public cancellation starts (see execute_cancel_src)
+    let current_time = env.block.time.seconds();
+    let stage = TimelockStage::SrcPublicCancellation;
+
+    if !immutables.timelocks.is_within_stage(current_time, stage) {
+        return Err(ContractError::TimelockNotExpired { 
+            stage: format!("{stage:?}"), 
+            required_at: immutables.timelocks.get_stage_time(stage), 
+            current: current_time, 
+        });
+    }
+
+    // Transfer tokens to maker (source behavior)
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    if escrow_state.balance > Uint128::zero() {
+        messages.push(principal_transfer_msg(&immutables.token, immutables.maker.as_str(), escrow_state.balance)?);
+    }
+
+    // The relayer earns at most the public caller reward, the rest of the
+    // safety deposit is refunded as in execute_cancel_src
+    let config = CONFIG.load(deps.storage)?;
+    let relayer_reward = escrow_state.native_balance.min(config.public_caller_reward);
+    if relayer_reward > Uint128::zero() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(relayer_reward.u128(), &escrow_state.escrow_info.safety_deposit_denom),
+        }));
+    }
+    messages.extend(deposit_refund_msgs(
+        &escrow_state.escrow_info,
+        escrow_state.native_balance - relayer_reward,
+        &escrow_state.escrow_info.creator,
+    ));
+
+    // Mark escrow as cancelled
+    escrow_state.escrow_info.status = EscrowStatus::Cancelled;
+    escrow_state.escrow_info.completed_at_height = Some(env.block.height);
+    ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    release_value(deps.storage, &escrow_state)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "cancel_src_signed")
+        .add_attribute("relayer_reward", relayer_reward.to_string())
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("order_hash", immutables.order_hash.clone())
+        .add_attribute("recipient", immutables.maker.to_string())
+        .add_attribute("relayer", info.sender.to_string()))
+}
+
+/// Cancel by agreement of both parties, ignoring the timelocks. The submitter
+/// stands in for itself; the counterparty's registered key must have signed.
+pub fn execute_mutual_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    counterparty_sig: Binary,
+) -> Result<Response, ContractError> {
+    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+
+    // State validation
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
+    }
+    ensure_funds_to_release(deps.as_ref(), &env, &escrow_state)?;
+    ensure_secret_not_revealed(&escrow_state, escrow_id)?;
+
+    let escrow_info = &escrow_state.escrow_info;
+    let immutables = &escrow_info.immutables;
+
+    // Access control: one party submits, the other signs
+    let counterparty_pubkey = if info.sender == immutables.maker {
+        &escrow_info.taker_pubkey
+    } else if info.sender == immutables.taker {
+        &escrow_info.maker_pubkey
+    } else {
+        return Err(ContractError::Unauthorized { 
+            reason: "Only the maker or taker can submit a mutual cancel".to_string() 
+        });
+    };
+
+    // Signature validation against the key the counterparty registered at creation
+    let message_hash = mutual_cancel_hash(&env.contract.address, escrow_id);
+    let verified = counterparty_pubkey.as_ref().is_some_and(|pubkey| {
+        deps.api.secp256k1_verify(&message_hash, &counterparty_sig, pubkey).unwrap_or(false)
+    });
+    if !verified {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    // Principal goes back as on any cancellation, the deposit to the creator or contributors who posted it
+    let recipient = escrow_info.escrow_type.get_cancellation_recipient(&immutables.maker, &immutables.taker);
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    if escrow_state.balance > Uint128::zero() {
+        messages.push(principal_transfer_msg(&immutables.token, recipient.as_str(), escrow_state.balance)?);
+    }
+
+    messages.extend(deposit_refund_msgs(escrow_info, escrow_state.native_balance, &escrow_info.creator));
+
+    let order_hash = immutables.order_hash.clone();
+
+    // Mark escrow as cancelled
+    escrow_state.escrow_info.status = EscrowStatus::Cancelled;
+    escrow_state.escrow_info.completed_at_height = Some(env.block.height);
+    ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    release_value(deps.storage, &escrow_state)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "mutual_cancel")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("order_hash", order_hash)
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("submitter", info.sender.to_string()))
+}
+
+/// Destination-specific cancel function
+pub fn execute_cancel_dst(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+
+    // Validate escrow type
+    if !escrow_state.escrow_info.escrow_type.is_destination() {
+        return Err(ContractError::WrongEscrowType { 
+            expected: EscrowType::Destination, 
+            found: escrow_state.escrow_info.escrow_type, 
+        });
+    }
+
+    // Access control: destination cancellation authorization matrix
+    //
+    // | caller                | DstCancellation onwards |
+    // |-----------------------|-------------------------|
+    // | taker                 | allowed                 |
+    // | maker                 | allowed                 |
+    // | creator (funder)      | allowed                 |
+    // | other                 | rejected                |
+    //
+    // Funds always go back to whoever posted them, so an abandoned swap can be
+    // unstuck by the maker or whoever funded the escrow without waiting for rescue.
+    let escrow_info = &escrow_state.escrow_info;
+    let immutables = &escrow_info.immutables;
+    if ![&immutables.taker, &immutables.maker, &escrow_info.creator].contains(&&info.sender) {
+        return Err(ContractError::Unauthorized {
+            reason: "Only the taker, maker or creator can cancel a destination escrow".to_string(),
+        });
+    }
+
+    // State validation
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
+    }
+    ensure_funds_to_release(deps.as_ref(), &env, &escrow_state)?;
+    ensure_secret_not_revealed(&escrow_state, escrow_id)?;
+
+    let immutables = &escrow_state.escrow_info.immutables;
+    
+    // Timelock validation
+    let current_time = env.block.time.seconds();
+    let stage = escrow_state.escrow_info.escrow_type.get_cancellation_stage();
+
+    if !immutables.timelocks.is_within_stage(current_time, stage) {
+        return Err(ContractError::TimelockNotExpired { 
+            stage: format!("{stage:?}"), 
+            required_at: immutables.timelocks.get_stage_time(stage), 
+            current: current_time, 
+        });
+    }
+
+    // Transfer tokens to taker (destination behavior)
+    let mut messages: Vec<CosmosMsg> = vec![];
+
+    if escrow_state.balance > Uint128::zero() {
+        messages.push(principal_transfer_msg(&immutables.token, immutables.taker.as_str(), escrow_state.balance)?);
+    }
+
+    // Return the safety deposit to the creator or contributors who posted it;
+    // only a public cancellation pays it to whoever submits
+    messages.extend(deposit_refund_msgs(&escrow_state.escrow_info, escrow_state.native_balance, &escrow_state.escrow_info.creator));
+
+    // Mark escrow as cancelled
+    escrow_state.escrow_info.status = EscrowStatus::Cancelled;
+    escrow_state.escrow_info.completed_at_height = Some(env.block.height);
+    ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    release_value(deps.storage, &escrow_state)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("method", "cancel_dst")
+        .add_attribute("escrow_id", escrow_id.to_string())
+        .add_attribute("order_hash", immutables.order_hash.clone())
+        .add_attribute("recipient", immutables.taker.to_string()))
+}
+
+/// Source-specific public withdraw function
+pub fn execute_public_withdraw_src(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    proof_sig: Option<Binary>,
+    secret: Option<String>,
+) -> Result<Response, ContractError> {
+    let escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+
+    // Validate escrow type
+    if !escrow_state.escrow_info.escrow_type.is_source() {
+        return Err(ContractError::WrongEscrowType { 
+            expected: EscrowType::Source, 
+            found: escrow_state.escrow_info.escrow_type, 
+        });
+    }
+
+    // Access control: public withdrawal authorization matrix
+    //
+    // | caller              | SrcPublicWithdrawal | DstPublicWithdrawal |
+    // |---------------------|---------------------|---------------------|
+    // | access token holder | allowed             | allowed             |
+    // | authority proof     | allowed             | allowed             |
+    // | recipient + secret  | taker (allowed)     | maker (allowed)     |
+    // | anyone else         | rejected            | rejected            |
+    //
+    // The recipient party can self-trigger without holding an access token,
+    // but only by presenting the secret, checked exactly as on the private path.
+    let config = CONFIG.load(deps.storage)?;
+    let is_recipient = info.sender == escrow_state.escrow_info.immutables.taker
+        && verify_recipient_secret(deps.as_ref(), &escrow_state, secret)?;
+    let has_proof = verify_authority_proof(deps.as_ref(), &env, &config, escrow_id, &info.sender, proof_sig)?;
+    let is_holder = holds_access_token(deps.as_ref(), &config, &escrow_state.escrow_info, &info.sender)?;
+    if !is_holder && !is_recipient && !has_proof {
+        return Err(ContractError::OnlyAccessTokenHolder {});
+    }
+
+    public_withdraw_src(deps, env, info, escrow_id, escrow_state, "public_withdraw_src")
+}
+
+/// Check the secret a recipient party self-triggers a public withdrawal with,
+/// against the same length floor and hashlock as the private withdrawals.
+/// No secret simply grants nothing.
+fn verify_recipient_secret(deps: Deps, escrow_state: &EscrowState, secret: Option<String>) -> Result<bool, ContractError> {
+    let Some(secret) = secret else {
+        return Ok(false);
+    };
+    validate_secret_length(deps, &secret)?;
+    if compute_hashlock(&secret) != escrow_state.escrow_info.immutables.hashlock {
+        return Err(ContractError::InvalidSecret {});
+    }
+    Ok(true)
+}
+
+/// Whether `caller` counts as an access token holder: a CW20 `access_token`'s
+/// own address, or, with a non-zero threshold, any account holding at least
+/// that much of the `access_token` CW20 or native denom
+fn holds_access_token(deps: Deps, config: &Config, escrow_info: &EscrowInfo, caller: &Addr) -> StdResult<bool> {
+    if matches!(&config.access_token, AccessToken::Cw20(address) if address == caller) {
+        return Ok(true);
+    }
+    let threshold = escrow_info.effective_access_token_threshold(config.default_access_token_threshold);
+    if threshold.is_zero() {
+        return Ok(false);
+    }
+    let balance = match &config.access_token {
+        AccessToken::Cw20(address) => {
+            let response: BalanceResponse = deps.querier.query_wasm_smart(
+                address.to_string(),
+                &Cw20QueryMsg::Balance { address: caller.to_string() },
+            )?;
+            response.balance
+        }
+        AccessToken::Native(denom) => deps.querier.query_balance(caller, denom)?.amount,
+    };
+    Ok(balance >= threshold)
+}
+
+/// Public withdrawal authorized by a maker signature for this caller, so
+/// relayers don't need the access token. The taker can't authorize it: the
+/// payout is its own, and nothing here asks for the secret.
+pub fn execute_public_withdraw_src_authorized(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    authorization_sig: Binary,
+) -> Result<Response, ContractError> {
+    let escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+
+    // Validate escrow type
+    if !escrow_state.escrow_info.escrow_type.is_source() {
+        return Err(ContractError::WrongEscrowType { 
+            expected: EscrowType::Source, 
+            found: escrow_state.escrow_info.escrow_type, 
+        });
+    }
+
+    // Signature validation: only the maker, whose principal is released, may authorize this caller
+    let message_hash = public_withdraw_authorization_hash(&env.contract.address, escrow_id, &info.sender);
+    let verified = escrow_state.escrow_info.maker_pubkey
+        .as_ref()
+        .is_some_and(|pubkey| deps.api.secp256k1_verify(&message_hash, &authorization_sig, pubkey).unwrap_or(false));
+    if !verified {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    public_withdraw_src(deps, env, info, escrow_id, escrow_state, "public_withdraw_src_authorized")
+}
+
+/// Check a public authority proof admitting `caller` to the public actions of
+/// an escrow. A supplied proof must verify; no proof simply grants nothing.
+fn verify_authority_proof(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    escrow_id: u64,
+    caller: &Addr,
+    proof_sig: Option<Binary>,
+) -> Result<bool, ContractError> {
+    let Some(proof_sig) = proof_sig else {
+        return Ok(false);
+    };
+    let pubkey = config.public_authority_pubkey.as_ref().ok_or(ContractError::InvalidSignature {})?;
+    let message_hash = public_authority_proof_hash(&env.contract.address, escrow_id, caller);
+    let verified = deps.api
+        .secp256k1_verify(&message_hash, &proof_sig, pubkey)
+        .map_err(|_| ContractError::InvalidSignature {})?;
+    if !verified {
+        return Err(ContractError::InvalidSignature {});
+    }
+    Ok(true)
+}
+
+/// Shared tail of the public source withdrawals, after access control
+fn public_withdraw_src(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    mut escrow_state: EscrowState,
+    method: &str,
+) -> Result<Response, ContractError> {
+    // State validation
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
     }
+    ensure_funds_to_release(deps.as_ref(), &env, &escrow_state)?;
+    ensure_principal_funded(&escrow_state, escrow_id)?;
 
+    let immutables = escrow_state.escrow_info.immutables.clone();
+    
     // Timelock validation
     let current_time = env.block.time.seconds();
-    let stage = escrow_state.escrow_info.escrow_type.get_withdrawal_stage();
+    let stage = escrow_state.escrow_info.escrow_type.get_public_withdrawal_stage();
 
     if !immutables.timelocks.is_within_stage(current_time, stage) {
         return Err(ContractError::TimelockNotExpired { 
+            stage: format!("{stage:?}"), 
+            required_at: immutables.timelocks.get_stage_time(stage), 
+            current: current_time, 
+        });
+    }
+
+    // Withdrawal windows close once cancellation opens, give or take the configured skew
+    let skew = CONFIG.load(deps.storage)?.timelock_skew_seconds;
+    if !immutables.timelocks.is_within_window_with_grace(current_time, stage, skew) {
+        return Err(ContractError::TimelockExpired { 
             stage: format!("{stage:?}") 
         });
     }
 
-    // Transfer tokens to maker (destination behavior)
+    // A configured deadline closes withdrawals early, even mid-window
+    if escrow_state.escrow_info.is_past_withdrawal_deadline(current_time) {
+        return Err(ContractError::TimelockExpired { 
+            stage: "Withdrawal deadline".to_string() 
+        });
+    }
+
+    // Transfer tokens to taker (source behavior)
     let mut messages: Vec<CosmosMsg> = vec![];
 
     if escrow_state.balance > Uint128::zero() {
-        if immutables.token == Addr::unchecked("") {
-            messages.push(CosmosMsg::Bank(BankMsg::Send {
-                to_address: immutables.maker.to_string(),
-                amount: coins(escrow_state.balance.u128(), "uatom"),
-            }));
-        } else {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: immutables.token.to_string(),
-                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                    recipient: immutables.maker.to_string(),
-                    amount: escrow_state.balance,
-                })?,
-                funds: vec![],
-            }));
-        }
+        messages.push(principal_transfer_msg(&immutables.token, immutables.taker.as_str(), escrow_state.balance)?);
     }
 
-    // Transfer safety deposit to caller
+    // Withdraw fee goes to the treasury, the rest of the safety deposit to the caller
+    let withdraw_fee = take_withdraw_fee(deps.storage, &mut escrow_state, &mut messages)?;
     if escrow_state.native_balance > Uint128::zero() {
         messages.push(CosmosMsg::Bank(BankMsg::Send {
             to_address: info.sender.to_string(),
-            amount: coins(escrow_state.native_balance.u128(), "uatom"),
+            amount: coins(escrow_state.native_balance.u128(), &escrow_state.escrow_info.safety_deposit_denom),
         }));
     }
 
-    // Mark escrow as inactive
-    escrow_state.escrow_info.is_active = false;
+    // Mark escrow as withdrawn
+    escrow_state.escrow_info.status = EscrowStatus::Withdrawn;
+    escrow_state.escrow_info.completed_at_height = Some(env.block.height);
     ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    release_value(deps.storage, &escrow_state)?;
 
     Ok(Response::new()
         .add_messages(messages)
-        .add_attribute("method", "withdraw_dst")
+        .add_attribute("method", method)
         .add_attribute("escrow_id", escrow_id.to_string())
-        .add_attribute("recipient", immutables.maker.to_string())
-        .add_attribute("secret", secret))
+        .add_attribute("order_hash", immutables.order_hash.clone())
+        .add_attribute("recipient", immutables.taker.to_string())
+        .add_attribute("withdraw_fee", withdraw_fee.to_string()))
 }
 
-/// Source-specific cancel function
-pub fn execute_cancel_src(
+/// Destination-specific public withdraw function
+pub fn execute_public_withdraw_dst(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     escrow_id: u64,
+    proof_sig: Option<Binary>,
+    secret: Option<String>,
 ) -> Result<Response, ContractError> {
     let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
         .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
 
     // Validate escrow type
-    if !escrow_state.escrow_info.escrow_type.is_source() {
-        return Err(ContractError::InvalidImmutables { 
-            reason: "This operation is only valid for source escrows".to_string() 
+    if !escrow_state.escrow_info.escrow_type.is_destination() {
+        return Err(ContractError::WrongEscrowType { 
+            expected: EscrowType::Destination, 
+            found: escrow_state.escrow_info.escrow_type, 
         });
     }
 
-    // Access control: only taker can cancel
-    if info.sender != escrow_state.escrow_info.immutables.taker {
-        return Err(ContractError::OnlyTaker {});
+    // Access control: access token holder or the maker (recipient) with the
+    // secret, see the authorization matrix on execute_public_withdraw_src
+    let config = CONFIG.load(deps.storage)?;
+    let is_recipient = info.sender == escrow_state.escrow_info.immutables.maker
+        && verify_recipient_secret(deps.as_ref(), &escrow_state, secret)?;
+    let has_proof = verify_authority_proof(deps.as_ref(), &env, &config, escrow_id, &info.sender, proof_sig)?;
+    let is_holder = holds_access_token(deps.as_ref(), &config, &escrow_state.escrow_info, &info.sender)?;
+    if !is_holder && !is_recipient && !has_proof {
+        return Err(ContractError::OnlyAccessTokenHolder {});
     }
 
     // State validation
-    if !escrow_state.escrow_info.is_active {
-        return Err(ContractError::EscrowNotActive { escrow_id });
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
     }
+    ensure_funds_to_release(deps.as_ref(), &env, &escrow_state)?;
+    ensure_principal_funded(&escrow_state, escrow_id)?;
 
-    let immutables = &escrow_state.escrow_info.immutables;
+    let immutables = escrow_state.escrow_info.immutables.clone();
     
     // Timelock validation
     let current_time = env.block.time.seconds();
-    let stage = escrow_state.escrow_info.escrow_type.get_cancellation_stage();
+    let stage = escrow_state.escrow_info.escrow_type.get_public_withdrawal_stage();
 
     if !immutables.timelocks.is_within_stage(current_time, stage) {
         return Err(ContractError::TimelockNotExpired { 
+            stage: format!("{stage:?}"), 
+            required_at: immutables.timelocks.get_stage_time(stage), 
+            current: current_time, 
+        });
+    }
+
+    // Withdrawal windows close once cancellation opens, give or take the configured skew
+    let skew = config.timelock_skew_seconds;
+    if !immutables.timelocks.is_within_window_with_grace(current_time, stage, skew) {
+        return Err(ContractError::TimelockExpired { 
             stage: format!("{stage:?}") 
         });
     }
 
-    // Transfer tokens to maker (source behavior)
+    // A configured deadline closes withdrawals early, even mid-window
+    if escrow_state.escrow_info.is_past_withdrawal_deadline(current_time) {
+        return Err(ContractError::TimelockExpired { 
+            stage: "Withdrawal deadline".to_string() 
+        });
+    }
+
+    // Transfer tokens to maker (destination behavior)
     let mut messages: Vec<CosmosMsg> = vec![];
 
     if escrow_state.balance > Uint128::zero() {
-        if immutables.token == Addr::unchecked("") {
-            messages.push(CosmosMsg::Bank(BankMsg::Send {
-                to_address: immutables.maker.to_string(),
-                amount: coins(escrow_state.balance.u128(), "uatom"),
-            }));
-        } else {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: immutables.token.to_string(),
-                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                    recipient: immutables.maker.to_string(),
-                    amount: escrow_state.balance,
-                })?,
-                funds: vec![],
-            }));
-        }
+        messages.push(principal_transfer_msg(&immutables.token, immutables.maker.as_str(), escrow_state.balance)?);
     }
 
-    // Transfer safety deposit to caller
+    // Withdraw fee goes to the treasury, the rest of the safety deposit to the caller
+    let withdraw_fee = take_withdraw_fee(deps.storage, &mut escrow_state, &mut messages)?;
     if escrow_state.native_balance > Uint128::zero() {
         messages.push(CosmosMsg::Bank(BankMsg::Send {
             to_address: info.sender.to_string(),
-            amount: coins(escrow_state.native_balance.u128(), "uatom"),
+            amount: coins(escrow_state.native_balance.u128(), &escrow_state.escrow_info.safety_deposit_denom),
         }));
     }
 
-    // Mark escrow as inactive
-    escrow_state.escrow_info.is_active = false;
+    // Mark escrow as withdrawn
+    escrow_state.escrow_info.status = EscrowStatus::Withdrawn;
+    escrow_state.escrow_info.completed_at_height = Some(env.block.height);
     ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    release_value(deps.storage, &escrow_state)?;
 
     Ok(Response::new()
         .add_messages(messages)
-        .add_attribute("method", "cancel_src")
+        .add_attribute("method", "public_withdraw_dst")
         .add_attribute("escrow_id", escrow_id.to_string())
-        .add_attribute("recipient", immutables.maker.to_string()))
+        .add_attribute("order_hash", immutables.order_hash.clone())
+        .add_attribute("recipient", immutables.maker.to_string())
+        .add_attribute("withdraw_fee", withdraw_fee.to_string()))
 }
 
-/// Destination-specific cancel function
-pub fn execute_cancel_dst(
+/// Source-specific public cancel function
+pub fn execute_public_cancel_src(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     escrow_id: u64,
+    proof_sig: Option<Binary>,
 ) -> Result<Response, ContractError> {
-    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
+    let escrow_state = ESCROWS.load(deps.storage, escrow_id)
         .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
 
     // Validate escrow type
-    if !escrow_state.escrow_info.escrow_type.is_destination() {
-        return Err(ContractError::InvalidImmutables { 
-            reason: "This operation is only valid for destination escrows".to_string() 
+    if !escrow_state.escrow_info.escrow_type.is_source() {
+        return Err(ContractError::WrongEscrowType { 
+            expected: EscrowType::Source, 
+            found: escrow_state.escrow_info.escrow_type, 
         });
     }
 
-    // Access control: only taker can cancel
-    if info.sender != escrow_state.escrow_info.immutables.taker {
-        return Err(ContractError::OnlyTaker {});
+    // Access control: only access token holder (or an authority-approved caller) can public cancel
+    let config = CONFIG.load(deps.storage)?;
+    let has_proof = verify_authority_proof(deps.as_ref(), &env, &config, escrow_id, &info.sender, proof_sig)?;
+    let is_holder = holds_access_token(deps.as_ref(), &config, &escrow_state.escrow_info, &info.sender)?;
+    if !is_holder && !has_proof {
+        return Err(ContractError::OnlyAccessTokenHolder {});
+    }
+
+    public_cancel_src(deps, env, info, escrow_id, escrow_state, &config, "public_cancel_src")
+}
+
+/// Cancel a source escrow for the maker once public cancellation has been open
+/// for the rescue delay, to any caller so keepers can automate the refund
+/// without an access token. Until then PublicCancelSrc and its reward stay
+/// with access token holders and authority-approved resolvers.
+pub fn execute_keeper_cancel(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+) -> Result<Response, ContractError> {
+    let escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+
+    if !escrow_state.escrow_info.escrow_type.is_source() {
+        return Err(ContractError::WrongEscrowType {
+            expected: EscrowType::Source,
+            found: escrow_state.escrow_info.escrow_type,
+        });
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let current_time = env.block.time.seconds();
+    let timelocks = &escrow_state.escrow_info.immutables.timelocks;
+    let rescue_delay = escrow_state.escrow_info.effective_rescue_delay(config.rescue_delay);
+    if !timelocks.is_rescue_available(current_time, rescue_delay) {
+        return Err(ContractError::TimelockNotExpired { 
+            stage: "Rescue delay not expired".to_string(), 
+            required_at: timelocks.rescue_start(rescue_delay), 
+            current: current_time, 
+        });
     }
 
+    public_cancel_src(deps, env, info, escrow_id, escrow_state, &config, "keeper_cancel")
+}
+
+/// Shared tail of the public source cancellations, after access control
+fn public_cancel_src(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    escrow_id: u64,
+    mut escrow_state: EscrowState,
+    config: &Config,
+    method: &str,
+) -> Result<Response, ContractError> {
     // State validation
-    if !escrow_state.escrow_info.is_active {
-        return Err(ContractError::EscrowNotActive { escrow_id });
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
     }
+    ensure_funds_to_release(deps.as_ref(), &env, &escrow_state)?;
+    ensure_secret_not_revealed(&escrow_state, escrow_id)?;
 
-    let immutables = &escrow_state.escrow_info.immutables;
+    let immutables = escrow_state.escrow_info.immutables.clone();
     
     // Timelock validation
     let current_time = env.block.time.seconds();
-    let stage = escrow_state.escrow_info.escrow_type.get_cancellation_stage();
+    let stage = escrow_state.escrow_info.escrow_type.get_public_cancellation_stage()
+        .ok_or_else(|| ContractError::InvalidImmutables { 
+            reason: "Public cancellation not supported for this escrow type".to_string() 
+        })?;
 
     if !immutables.timelocks.is_within_stage(current_time, stage) {
         return Err(ContractError::TimelockNotExpired { 
-            stage: format!("{stage:?}") 
+            stage: format!("{stage:?}"), 
+            required_at: immutables.timelocks.get_stage_time(stage), 
+            current: current_time, 
         });
     }
 
-    // Transfer tokens to taker (destination behavior)
+    // Transfer tokens to maker (source behavior)
     let mut messages: Vec<CosmosMsg> = vec![];
 
     if escrow_state.balance > Uint128::zero() {
-        if immutables.token == Addr::unchecked("") {
-            messages.push(CosmosMsg::Bank(BankMsg::Send {
-                to_address: immutables.taker.to_string(),
-                amount: coins(escrow_state.balance.u128(), "uatom"),
-            }));
-        } else {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: immutables.token.to_string(),
-                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                    recipient: immutables.taker.to_string(),
-                    amount: escrow_state.balance,
-                })?,
-                funds: vec![],
-            }));
-        }
+        messages.push(principal_transfer_msg(&immutables.token, immutables.maker.as_str(), escrow_state.balance)?);
     }
 
-    // Transfer safety deposit to caller
-    if escrow_state.native_balance > Uint128::zero() {
+    // Pay the public caller at most the configured reward, the rest of the
+    // safety deposit goes back to whoever posted it
+    let caller_reward = escrow_state.native_balance.min(config.public_caller_reward);
+    escrow_state.debit_deposit(caller_reward)?;
+    let deposit_remainder = escrow_state.native_balance;
+    escrow_state.debit_deposit(deposit_remainder)?;
+
+    if caller_reward > Uint128::zero() {
         messages.push(CosmosMsg::Bank(BankMsg::Send {
             to_address: info.sender.to_string(),
-            amount: coins(escrow_state.native_balance.u128(), "uatom"),
+            amount: coins(caller_reward.u128(), &escrow_state.escrow_info.safety_deposit_denom),
         }));
     }
+    messages.extend(deposit_refund_msgs(&escrow_state.escrow_info, deposit_remainder, &escrow_state.escrow_info.creator));
 
-    // Mark escrow as inactive
-    escrow_state.escrow_info.is_active = false;
+    // Mark escrow as cancelled
+    escrow_state.escrow_info.status = EscrowStatus::Cancelled;
+    escrow_state.escrow_info.completed_at_height = Some(env.block.height);
     ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    release_value(deps.storage, &escrow_state)?;
 
     Ok(Response::new()
         .add_messages(messages)
-        .add_attribute("method", "cancel_dst")
+        .add_attribute("method", method)
+        .add_attribute("caller_reward", caller_reward.to_string())
         .add_attribute("escrow_id", escrow_id.to_string())
-        .add_attribute("recipient", immutables.taker.to_string()))
+        .add_attribute("order_hash", immutables.order_hash.clone())
+        .add_attribute("recipient", immutables.maker.to_string()))
 }
 
-/// Source-specific public withdraw function
-pub fn execute_public_withdraw_src(
+/// Rescue function for emergency fund recovery
+pub fn execute_rescue(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
@@ -450,78 +2071,71 @@ pub fn execute_public_withdraw_src(
     let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
         .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
 
-    // Validate escrow type
-    if !escrow_state.escrow_info.escrow_type.is_source() {
-        return Err(ContractError::InvalidImmutables { 
-            reason: "This operation is only valid for source escrows".to_string() 
+    // State validation
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
         });
     }
 
-    // Access control: only access token holder can public withdraw
-    let config = CONFIG.load(deps.storage)?;
-    if info.sender != config.access_token { // TODO:FIX access token holder
-        return Err(ContractError::OnlyAccessTokenHolder {});
-    }
-
-    // State validation
-    if !escrow_state.escrow_info.is_active {
-        return Err(ContractError::EscrowNotActive { escrow_id });
+    // Access control: only taker can rescue funds
+    if info.sender != escrow_state.escrow_info.immutables.taker {
+        return Err(ContractError::OnlyTaker {});
     }
 
     let immutables = &escrow_state.escrow_info.immutables;
     
-    // Timelock validation
+    // Rescue delay validation (per-escrow override takes precedence)
+    let config = CONFIG.load(deps.storage)?;
     let current_time = env.block.time.seconds();
-    let stage = escrow_state.escrow_info.escrow_type.get_public_withdrawal_stage();
-
-    if !immutables.timelocks.is_within_stage(current_time, stage) {
+    let rescue_delay = escrow_state.escrow_info.effective_rescue_delay(config.rescue_delay);
+    
+    if !immutables.timelocks.is_rescue_available(current_time, rescue_delay) {
         return Err(ContractError::TimelockNotExpired { 
-            stage: format!("{stage:?}") 
+            stage: "Rescue delay not expired".to_string(), 
+            required_at: immutables.timelocks.rescue_start(rescue_delay), 
+            current: current_time, 
         });
     }
 
-    // Transfer tokens to taker (source behavior)
+    // Transfer all funds to caller (taker)
     let mut messages: Vec<CosmosMsg> = vec![];
 
     if escrow_state.balance > Uint128::zero() {
-        if immutables.token == Addr::unchecked("") {
-            messages.push(CosmosMsg::Bank(BankMsg::Send {
-                to_address: immutables.taker.to_string(),
-                amount: coins(escrow_state.balance.u128(), "uatom"),
-            }));
-        } else {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: immutables.token.to_string(),
-                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                    recipient: immutables.taker.to_string(),
-                    amount: escrow_state.balance,
-                })?,
-                funds: vec![],
-            }));
-        }
+        messages.push(principal_transfer_msg(&immutables.token, info.sender.as_str(), escrow_state.balance)?);
     }
 
-    // Transfer safety deposit to caller
-    if escrow_state.native_balance > Uint128::zero() {
+    // A crowdfunded deposit goes back to its contributors rather than the caller
+    messages.extend(deposit_refund_msgs(&escrow_state.escrow_info, escrow_state.native_balance, &info.sender));
+
+    // Sweep other denoms sent to the contract, honouring the allowlist
+    let stray = stray_rescue_funds(deps.as_ref(), &env, &config, &escrow_state)?;
+    if !stray.is_empty() {
         messages.push(CosmosMsg::Bank(BankMsg::Send {
             to_address: info.sender.to_string(),
-            amount: coins(escrow_state.native_balance.u128(), "uatom"),
+            amount: stray,
         }));
     }
 
-    // Mark escrow as inactive
-    escrow_state.escrow_info.is_active = false;
+    // Mark escrow as rescued
+    escrow_state.escrow_info.status = EscrowStatus::Rescued;
+    escrow_state.escrow_info.completed_at_height = Some(env.block.height);
     ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    release_value(deps.storage, &escrow_state)?;
 
     Ok(Response::new()
         .add_messages(messages)
-        .add_attribute("method", "public_withdraw_src")
+        .add_attribute("method", "rescue")
         .add_attribute("escrow_id", escrow_id.to_string())
-        .add_attribute("recipient", immutables.taker.to_string()))
+        .add_attribute("order_hash", immutables.order_hash.clone())
+        .add_attribute("recipient", info.sender.to_string()))
 }
 
-/// Destination-specific public withdraw function
-pub fn execute_public_withdraw_dst(
+/// Keeper sweep of an escrow nobody acted on: once the final cancellation stage
+/// and the rescue delay have both passed, anyone may return the principal as a
+/// cancellation would and collect the safety deposit as a reward
+pub fn execute_sweep_expired(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
@@ -530,226 +2144,280 @@ pub fn execute_public_withdraw_dst(
     let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
         .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
 
-    // Validate escrow type
-    if !escrow_state.escrow_info.escrow_type.is_destination() {
-        return Err(ContractError::InvalidImmutables { 
-            reason: "This operation is only valid for destination escrows".to_string() 
-        });
-    }
-
-    // Access control: only access token holder can public withdraw
-    let config = CONFIG.load(deps.storage)?;
-    if info.sender != config.access_token {
-        return Err(ContractError::OnlyAccessTokenHolder {});
-    }
-
     // State validation
-    if !escrow_state.escrow_info.is_active {
-        return Err(ContractError::EscrowNotActive { escrow_id });
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
     }
+    ensure_funds_to_release(deps.as_ref(), &env, &escrow_state)?;
+    ensure_secret_not_revealed(&escrow_state, escrow_id)?;
 
     let immutables = &escrow_state.escrow_info.immutables;
-    
-    // Timelock validation
-    let current_time = env.block.time.seconds();
-    let stage = escrow_state.escrow_info.escrow_type.get_public_withdrawal_stage();
+    let escrow_type = escrow_state.escrow_info.escrow_type;
 
-    if !immutables.timelocks.is_within_stage(current_time, stage) {
+    // Timelock validation: every window must have closed
+    let config = CONFIG.load(deps.storage)?;
+    let current_time = env.block.time.seconds();
+    let final_stage = escrow_type.get_public_cancellation_stage()
+        .unwrap_or_else(|| escrow_type.get_cancellation_stage());
+    if !immutables.timelocks.is_within_stage(current_time, final_stage) {
         return Err(ContractError::TimelockNotExpired { 
-            stage: format!("{stage:?}") 
+            stage: format!("{final_stage:?}"), 
+            required_at: immutables.timelocks.get_stage_time(final_stage), 
+            current: current_time, 
+        });
+    }
+    let rescue_delay = escrow_state.escrow_info.effective_rescue_delay(config.rescue_delay);
+    if !immutables.timelocks.is_rescue_available(current_time, rescue_delay) {
+        return Err(ContractError::TimelockNotExpired { 
+            stage: "Rescue delay not expired".to_string(), 
+            required_at: immutables.timelocks.rescue_start(rescue_delay), 
+            current: current_time, 
         });
     }
 
-    // Transfer tokens to maker (destination behavior)
+    // Principal goes back as on cancellation, deposit rewards the keeper
+    let recipient = escrow_type.get_cancellation_recipient(&immutables.maker, &immutables.taker);
     let mut messages: Vec<CosmosMsg> = vec![];
 
     if escrow_state.balance > Uint128::zero() {
-        if immutables.token == Addr::unchecked("") {
-            messages.push(CosmosMsg::Bank(BankMsg::Send {
-                to_address: immutables.maker.to_string(),
-                amount: coins(escrow_state.balance.u128(), "uatom"),
-            }));
-        } else {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: immutables.token.to_string(),
-                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                    recipient: immutables.maker.to_string(),
-                    amount: escrow_state.balance,
-                })?,
-                funds: vec![],
-            }));
-        }
+        messages.push(principal_transfer_msg(&immutables.token, recipient.as_str(), escrow_state.balance)?);
     }
 
-    // Transfer safety deposit to caller
     if escrow_state.native_balance > Uint128::zero() {
         messages.push(CosmosMsg::Bank(BankMsg::Send {
             to_address: info.sender.to_string(),
-            amount: coins(escrow_state.native_balance.u128(), "uatom"),
+            amount: coins(escrow_state.native_balance.u128(), &escrow_state.escrow_info.safety_deposit_denom),
         }));
     }
 
-    // Mark escrow as inactive
-    escrow_state.escrow_info.is_active = false;
+    // Mark escrow as cancelled
+    escrow_state.escrow_info.status = EscrowStatus::Cancelled;
+    escrow_state.escrow_info.completed_at_height = Some(env.block.height);
     ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    release_value(deps.storage, &escrow_state)?;
 
     Ok(Response::new()
         .add_messages(messages)
-        .add_attribute("method", "public_withdraw_dst")
+        .add_attribute("method", "sweep_expired")
         .add_attribute("escrow_id", escrow_id.to_string())
-        .add_attribute("recipient", immutables.maker.to_string()))
+        .add_attribute("order_hash", immutables.order_hash.clone())
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("keeper", info.sender.to_string()))
 }
 
-/// Source-specific public cancel function
-pub fn execute_public_cancel_src(
+/// Owner-only emergency sweep of a stuck escrow, e.g. when the taker lost their key
+pub fn execute_admin_rescue(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     escrow_id: u64,
+    recipient: String,
 ) -> Result<Response, ContractError> {
-    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
-        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+    let config = CONFIG.load(deps.storage)?;
 
-    // Validate escrow type
-    if !escrow_state.escrow_info.escrow_type.is_source() {
-        return Err(ContractError::InvalidImmutables { 
-            reason: "This operation is only valid for source escrows".to_string() 
+    // Access control: only owner can use the emergency rescue
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized { 
+            reason: "Only owner can perform an admin rescue".to_string() 
         });
     }
 
-    // Access control: only access token holder can public cancel
-    let config = CONFIG.load(deps.storage)?;
-    if info.sender != config.access_token {
-        return Err(ContractError::OnlyAccessTokenHolder {});
-    }
+    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
+        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
 
     // State validation
-    if !escrow_state.escrow_info.is_active {
-        return Err(ContractError::EscrowNotActive { escrow_id });
+    if !escrow_state.escrow_info.status.is_active() {
+        return Err(ContractError::EscrowAlreadyCompleted { 
+            escrow_id, 
+            status: escrow_state.escrow_info.status 
+        });
     }
 
+    let recipient = deps.api.addr_validate(&recipient)?;
     let immutables = &escrow_state.escrow_info.immutables;
-    
-    // Timelock validation
-    let current_time = env.block.time.seconds();
-    let stage = escrow_state.escrow_info.escrow_type.get_public_cancellation_stage()
-        .ok_or_else(|| ContractError::InvalidImmutables { 
-            reason: "Public cancellation not supported for this escrow type".to_string() 
-        })?;
 
-    if !immutables.timelocks.is_within_stage(current_time, stage) {
+    // Admin rescue delay validation
+    let current_time = env.block.time.seconds();
+    if !immutables.timelocks.is_rescue_available(current_time, config.admin_rescue_delay) {
         return Err(ContractError::TimelockNotExpired { 
-            stage: format!("{stage:?}") 
+            stage: "Admin rescue delay not expired".to_string(), 
+            required_at: immutables.timelocks.rescue_start(config.admin_rescue_delay), 
+            current: current_time, 
         });
     }
 
-    // Transfer tokens to maker (source behavior)
+    // Transfer all funds to the recipient
     let mut messages: Vec<CosmosMsg> = vec![];
 
     if escrow_state.balance > Uint128::zero() {
-        if immutables.token == Addr::unchecked("") {
-            messages.push(CosmosMsg::Bank(BankMsg::Send {
-                to_address: immutables.maker.to_string(),
-                amount: coins(escrow_state.balance.u128(), "uatom"),
-            }));
-        } else {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: immutables.token.to_string(),
-                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                    recipient: immutables.maker.to_string(),
-                    amount: escrow_state.balance,
-                })?,
-                funds: vec![],
-            }));
-        }
+        messages.push(principal_transfer_msg(&immutables.token, recipient.as_str(), escrow_state.balance)?);
     }
 
-    // Transfer safety deposit to caller
     if escrow_state.native_balance > Uint128::zero() {
         messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: coins(escrow_state.native_balance.u128(), "uatom"),
+            to_address: recipient.to_string(),
+            amount: coins(escrow_state.native_balance.u128(), &escrow_state.escrow_info.safety_deposit_denom),
         }));
     }
 
-    // Mark escrow as inactive
-    escrow_state.escrow_info.is_active = false;
+    // Mark escrow as rescued by the admin
+    escrow_state.escrow_info.status = EscrowStatus::AdminRescued;
+    escrow_state.escrow_info.completed_at_height = Some(env.block.height);
     ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    release_value(deps.storage, &escrow_state)?;
 
     Ok(Response::new()
         .add_messages(messages)
-        .add_attribute("method", "public_cancel_src")
+        .add_attribute("method", "admin_rescue")
         .add_attribute("escrow_id", escrow_id.to_string())
-        .add_attribute("recipient", immutables.maker.to_string()))
+        .add_attribute("order_hash", immutables.order_hash.clone())
+        .add_attribute("recipient", recipient.to_string()))
 }
 
-/// Rescue function for emergency fund recovery
-pub fn execute_rescue(
+/// Native funds held by the contract beyond the escrow's own principal and
+/// deposit, excluding uncollected creation fees. When an allowlist is set,
+/// only listed denoms are returned.
+fn stray_rescue_funds(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    escrow_state: &EscrowState,
+) -> Result<Vec<Coin>, ContractError> {
+    let escrow_info = &escrow_state.escrow_info;
+    let fees = COLLECTED_FEES.may_load(deps.storage)?.unwrap_or_default();
+
+    let mut stray = vec![];
+    for coin in deps.querier.query_all_balances(&env.contract.address)? {
+        if coin.denom == escrow_info.immutables.token.as_str() || coin.denom == escrow_info.safety_deposit_denom {
+            continue;
+        }
+        if let Some(allowlist) = &config.rescue_denom_allowlist {
+            if !allowlist.contains(&coin.denom) {
+                continue;
+            }
+        }
+        let amount = if coin.denom == config.native_denom {
+            coin.amount.saturating_sub(fees)
+        } else {
+            coin.amount
+        };
+        if !amount.is_zero() {
+            stray.push(Coin { denom: coin.denom, amount });
+        }
+    }
+    Ok(stray)
+}
+
+/// Restrict which extra denoms a rescue forwards (owner only)
+pub fn execute_set_rescue_allowlist(
     deps: DepsMut,
-    env: Env,
     info: MessageInfo,
-    escrow_id: u64,
+    denoms: Option<Vec<String>>,
 ) -> Result<Response, ContractError> {
-    let mut escrow_state = ESCROWS.load(deps.storage, escrow_id)
-        .map_err(|_| ContractError::EscrowNotFound { escrow_id })?;
+    let mut config = CONFIG.load(deps.storage)?;
 
-    // State validation
-    if !escrow_state.escrow_info.is_active {
-        return Err(ContractError::EscrowNotActive { escrow_id });
+    // Access control: only owner can change the allowlist
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {
+            reason: "Only owner can set the rescue allowlist".to_string()
+        });
     }
 
-    // Access control: only taker can rescue funds
-    if info.sender != escrow_state.escrow_info.immutables.taker {
-        return Err(ContractError::OnlyTaker {});
+    if let Some(denoms) = &denoms {
+        for denom in denoms {
+            validate_denom(denom)?;
+        }
     }
 
-    let immutables = &escrow_state.escrow_info.immutables;
-    
-    // Rescue delay validation
+    let denom_count = denoms.as_ref().map_or(0, Vec::len);
+    config.rescue_denom_allowlist = denoms;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_rescue_allowlist")
+        .add_attribute("denom_count", denom_count.to_string()))
+}
+
+/// Nominate a new owner, who must accept before ownership moves (owner only)
+pub fn execute_propose_new_owner(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: String,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let current_time = env.block.time.seconds();
-    
-    if !immutables.timelocks.is_rescue_available(current_time, config.rescue_delay) {
-        return Err(ContractError::TimelockNotExpired { 
-            stage: "Rescue delay not expired".to_string() 
+
+    // Access control: only owner can nominate a successor
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {
+            reason: "Only owner can propose a new owner".to_string()
         });
     }
 
-    // Transfer all funds to caller (taker)
-    let mut messages: Vec<CosmosMsg> = vec![];
+    // A later proposal replaces an earlier one that was never accepted
+    let pending_owner = deps.api.addr_validate(&owner)?;
+    PENDING_OWNER.save(deps.storage, &pending_owner)?;
 
-    if escrow_state.balance > Uint128::zero() {
-        if immutables.token == Addr::unchecked("") {
-            messages.push(CosmosMsg::Bank(BankMsg::Send {
-                to_address: info.sender.to_string(),
-                amount: coins(escrow_state.balance.u128(), "uatom"),
-            }));
-        } else {
-            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: immutables.token.to_string(),
-                msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
-                    recipient: info.sender.to_string(),
-                    amount: escrow_state.balance,
-                })?,
-                funds: vec![],
-            }));
-        }
+    Ok(Response::new()
+        .add_attribute("method", "propose_new_owner")
+        .add_attribute("pending_owner", pending_owner))
+}
+
+/// Promote the pending owner (pending owner only)
+pub fn execute_accept_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending_owner = PENDING_OWNER.may_load(deps.storage)?;
+    if pending_owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {
+            reason: "Only the pending owner can accept ownership".to_string()
+        });
     }
 
-    if escrow_state.native_balance > Uint128::zero() {
-        messages.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: coins(escrow_state.native_balance.u128(), "uatom"),
-        }));
+    let mut config = CONFIG.load(deps.storage)?;
+    let previous_owner = std::mem::replace(&mut config.owner, info.sender);
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("method", "accept_ownership")
+        .add_attribute("previous_owner", previous_owner)
+        .add_attribute("owner", config.owner))
+}
+
+/// Withdraw accumulated creation fees (owner only)
+pub fn execute_withdraw_fees(
+    deps: DepsMut,
+    info: MessageInfo,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Access control: only owner can withdraw fees
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized { 
+            reason: "Only owner can withdraw fees".to_string() 
+        });
     }
 
-    // Mark escrow as inactive
-    escrow_state.escrow_info.is_active = false;
-    ESCROWS.save(deps.storage, escrow_id, &escrow_state)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let fees = COLLECTED_FEES.may_load(deps.storage)?.unwrap_or_default();
+
+    if fees.is_zero() {
+        return Err(ContractError::InvalidAmount { amount: fees.to_string() });
+    }
+
+    COLLECTED_FEES.save(deps.storage, &Uint128::zero())?;
 
     Ok(Response::new()
-        .add_messages(messages)
-        .add_attribute("method", "rescue")
-        .add_attribute("escrow_id", escrow_id.to_string())
-        .add_attribute("recipient", info.sender.to_string()))
-} 
\ No newline at end of file
+        .add_message(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(fees.u128(), &config.native_denom),
+        })
+        .add_attribute("method", "withdraw_fees")
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("amount", fees.to_string()))
+}
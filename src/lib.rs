@@ -3,15 +3,25 @@ use cosmwasm_std::{
     Response, StdResult,
 };
 
+use crate::contract::apply_event_prefix;
 use crate::error::ContractError;
 use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
 use crate::execute::{
     execute_instantiate, 
     execute_withdraw_src, execute_withdraw_dst, execute_cancel_src, execute_cancel_dst,
     execute_public_withdraw_src, execute_public_withdraw_dst, execute_public_cancel_src,
-    execute_rescue
+    execute_cancel_src_signed, execute_mutual_cancel, execute_reveal_secret, execute_keeper_cancel,
+    execute_extend_timelocks,
+    execute_rescue, execute_receive, execute_withdraw_fees, execute_top_up, execute_admin_rescue,
+    execute_set_rescue_allowlist, execute_receive_nft, execute_sweep_expired,
+    execute_public_withdraw_src_authorized, execute_propose_new_owner, execute_accept_ownership,
+};
+use crate::query::{
+    query_config, query_ownership, query_escrow_state, query_reconciliation, query_total_value_locked, query_metrics, query_escrows_by_order_hash, query_action_preview,
+    query_available_actions, query_completed_escrows, query_hashlock_for, query_can_withdraw, query_near_rescue, query_invariants, query_immutables_hash, query_compute_immutables_hash,
+    query_validate_params, query_rescue_status, query_actionable_escrows, query_pending_cancellations, query_escrow_by_swap_id,
+    query_escrows_by_maker, query_escrows_by_taker, query_escrows_by_height,
 };
-use crate::query::{query_config};
 
 pub mod contract;
 pub mod error;
@@ -22,16 +32,27 @@ pub mod state;
 
 #[entry_point]
 pub fn instantiate(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    execute_instantiate(deps, env, info, msg)
+    let response = execute_instantiate(deps.branch(), env, info, msg)?;
+    Ok(apply_event_prefix(deps.as_ref(), response)?)
 }
 
 #[entry_point]
 pub fn execute(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let response = dispatch_execute(deps.branch(), env, info, msg)?;
+    Ok(apply_event_prefix(deps.as_ref(), response)?)
+}
+
+fn dispatch_execute(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
@@ -39,28 +60,100 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         // Escrow operations
-        ExecuteMsg::WithdrawSrc { escrow_id, secret } => 
-            execute_withdraw_src(deps, env, info, escrow_id, secret),
+        ExecuteMsg::WithdrawSrc { escrow_id, secret, recipient } => 
+            execute_withdraw_src(deps, env, info, escrow_id, secret, recipient),
         ExecuteMsg::CancelSrc { escrow_id } => 
             execute_cancel_src(deps, env, info, escrow_id),
-        ExecuteMsg::PublicWithdrawSrc { escrow_id } => 
-            execute_public_withdraw_src(deps, env, info, escrow_id),
-        ExecuteMsg::PublicCancelSrc { escrow_id } => 
-            execute_public_cancel_src(deps, env, info, escrow_id),
+        ExecuteMsg::PublicWithdrawSrc { escrow_id, proof_sig, secret } => 
+            execute_public_withdraw_src(deps, env, info, escrow_id, proof_sig, secret),
+        ExecuteMsg::PublicCancelSrc { escrow_id, proof_sig } => 
+            execute_public_cancel_src(deps, env, info, escrow_id, proof_sig),
+        ExecuteMsg::KeeperCancel { escrow_id } => 
+            execute_keeper_cancel(deps, env, info, escrow_id),
+        ExecuteMsg::PublicWithdrawSrcAuthorized { escrow_id, authorization_sig } => 
+            execute_public_withdraw_src_authorized(deps, env, info, escrow_id, authorization_sig),
+        ExecuteMsg::CancelSrcSigned { escrow_id, maker_sig, maker_pubkey } => 
+            execute_cancel_src_signed(deps, env, info, escrow_id, maker_sig, maker_pubkey),
+        ExecuteMsg::ExtendTimelocks { escrow_id, additional_hours } => 
+            execute_extend_timelocks(deps, env, info, escrow_id, additional_hours),
+        ExecuteMsg::RevealSecret { escrow_id, secret } => 
+            execute_reveal_secret(deps, info, escrow_id, secret),
+        ExecuteMsg::MutualCancel { escrow_id, counterparty_sig } => 
+            execute_mutual_cancel(deps, env, info, escrow_id, counterparty_sig),
         ExecuteMsg::WithdrawDst { escrow_id, secret } => 
             execute_withdraw_dst(deps, env, info, escrow_id, secret),
         ExecuteMsg::CancelDst { escrow_id } => 
             execute_cancel_dst(deps, env, info, escrow_id),
-        ExecuteMsg::PublicWithdrawDst { escrow_id } => 
-            execute_public_withdraw_dst(deps, env, info, escrow_id),
+        ExecuteMsg::PublicWithdrawDst { escrow_id, proof_sig, secret } => 
+            execute_public_withdraw_dst(deps, env, info, escrow_id, proof_sig, secret),
         ExecuteMsg::Rescue { escrow_id } => 
             execute_rescue(deps, env, info, escrow_id),
+        ExecuteMsg::SweepExpired { escrow_id } => 
+            execute_sweep_expired(deps, env, info, escrow_id),
+        ExecuteMsg::TopUp { escrow_id } => 
+            execute_top_up(deps, env, info, escrow_id),
+        ExecuteMsg::Receive(wrapper) => 
+            execute_receive(deps, env, info, wrapper),
+        ExecuteMsg::ReceiveNft(wrapper) => 
+            execute_receive_nft(deps, info, wrapper),
+        // Admin operations
+        ExecuteMsg::WithdrawFees { recipient } => 
+            execute_withdraw_fees(deps, info, recipient),
+        ExecuteMsg::AdminRescue { escrow_id, recipient } => 
+            execute_admin_rescue(deps, env, info, escrow_id, recipient),
+        ExecuteMsg::SetRescueAllowlist { denoms } => 
+            execute_set_rescue_allowlist(deps, info, denoms),
+        ExecuteMsg::ProposeNewOwner { owner } => 
+            execute_propose_new_owner(deps, info, owner),
+        ExecuteMsg::AcceptOwnership {} => 
+            execute_accept_ownership(deps, info),
     }
 }
 
 #[entry_point]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::Ownership {} => to_json_binary(&query_ownership(deps)?),
+        QueryMsg::EscrowState {} => to_json_binary(&query_escrow_state(deps, env)?),
+        QueryMsg::Reconciliation {} => to_json_binary(&query_reconciliation(deps)?),
+        QueryMsg::TotalValueLocked {} => to_json_binary(&query_total_value_locked(deps)?),
+        QueryMsg::Metrics {} => to_json_binary(&query_metrics(deps)?),
+        QueryMsg::EscrowsByOrderHash { order_hash } => 
+            to_json_binary(&query_escrows_by_order_hash(deps, env, order_hash)?),
+        QueryMsg::EscrowBySwapId { swap_id } => 
+            to_json_binary(&query_escrow_by_swap_id(deps, env, swap_id)?),
+        QueryMsg::EscrowsByMaker { maker, start_after, limit } => 
+            to_json_binary(&query_escrows_by_maker(deps, env, maker, start_after, limit)?),
+        QueryMsg::EscrowsByTaker { taker, start_after, limit } => 
+            to_json_binary(&query_escrows_by_taker(deps, env, taker, start_after, limit)?),
+        QueryMsg::EscrowsByHeight { from_height, to_height, start_after, limit } => 
+            to_json_binary(&query_escrows_by_height(deps, env, from_height, to_height, start_after, limit)?),
+        QueryMsg::ActionPreview { escrow_id, action } => 
+            to_json_binary(&query_action_preview(deps, env, escrow_id, action)?),
+        QueryMsg::AvailableActions { escrow_id } => 
+            to_json_binary(&query_available_actions(deps, env, escrow_id)?),
+        QueryMsg::ValidateParams { params } => 
+            to_json_binary(&query_validate_params(deps, env, *params)?),
+        QueryMsg::HashlockFor { secret } => 
+            to_json_binary(&query_hashlock_for(secret)?),
+        QueryMsg::CanWithdraw { escrow_id, secret } => 
+            to_json_binary(&query_can_withdraw(deps, env, escrow_id, secret)?),
+        QueryMsg::ImmutablesHash { escrow_id } => 
+            to_json_binary(&query_immutables_hash(deps, escrow_id)?),
+        QueryMsg::ComputeImmutablesHash { params } => 
+            to_json_binary(&query_compute_immutables_hash(params)?),
+        QueryMsg::CompletedEscrows { start_after, limit, status } => 
+            to_json_binary(&query_completed_escrows(deps, start_after, limit, status)?),
+        QueryMsg::NearRescue { within_seconds, start_after, limit } => 
+            to_json_binary(&query_near_rescue(deps, env, within_seconds, start_after, limit)?),
+        QueryMsg::Invariants { start_after, limit } => 
+            to_json_binary(&query_invariants(deps, start_after, limit)?),
+        QueryMsg::RescueStatus { escrow_id } => 
+            to_json_binary(&query_rescue_status(deps, env, escrow_id)?),
+        QueryMsg::ActionableEscrows { start_after, limit } => 
+            to_json_binary(&query_actionable_escrows(deps, env, start_after, limit)?),
+        QueryMsg::PendingCancellations { start_after, limit } => 
+            to_json_binary(&query_pending_cancellations(deps, env, start_after, limit)?),
     }
 } 
\ No newline at end of file
@@ -1,6 +1,7 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Uint128;
-use crate::state::{PackedTimelocks, EscrowType};
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+use crate::state::{AssetTotal, Immutables, PackedTimelocks, EscrowType};
+use cw20::Cw20ReceiveMsg;
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -16,6 +17,68 @@ pub struct InstantiateMsg {
     pub dst_token: String,
     pub dst_amount: Uint128,
     pub escrow_type: EscrowType,
+    // Contract configuration
+    pub access_token: UncheckedAccessToken,
+    pub rescue_delay: u64,
+    /// Fee paid to the owner on top of the escrowed funds
+    pub creation_fee: Uint128,
+    /// Per-escrow rescue delay, falls back to `rescue_delay` when not set
+    pub rescue_delay_override: Option<u64>,
+    /// Maximum share of the safety deposit paid to a public canceller
+    pub public_caller_reward: Uint128,
+    /// Delay after deployment before the owner can sweep a stuck escrow, must exceed the rescue delay
+    pub admin_rescue_delay: u64,
+    /// Compressed secp256k1 key whose signatures grant public-action eligibility
+    pub public_authority_pubkey: Option<Binary>,
+    /// Denom for native funds, defaults to `uatom`; IBC vouchers (`ibc/<hash>`) are accepted
+    pub native_denom: Option<String>,
+    /// Denom for the safety deposit, defaults to `native_denom`
+    pub safety_deposit_denom: Option<String>,
+    /// Memo carried by a later IBC transfer of the escrowed funds
+    pub ibc_memo: Option<String>,
+    /// Compressed secp256k1 key the maker signs gasless cancellations and public withdrawal authorizations with
+    pub maker_pubkey: Option<Binary>,
+    /// NFT locked instead of a fungible principal; `token` is then its CW721 contract and `amount` must be 1
+    pub token_id: Option<String>,
+    /// Minimum byte length of the revealed secret, defaults to 32 and may only be raised
+    pub min_secret_bytes: Option<u8>,
+    /// Compressed secp256k1 key the taker signs public withdrawal authorizations with
+    pub taker_pubkey: Option<Binary>,
+    /// Hours after deployment after which withdrawals are rejected even inside a window
+    pub withdrawal_deadline_offset: Option<u8>,
+    /// Fee taken from the safety deposit on withdrawal, capped at the deposit
+    pub withdraw_fee: Uint128,
+    /// Address receiving withdraw fees, defaults to the owner
+    pub treasury: Option<String>,
+    /// Maker signature over `order_commitment_hash`, checked against `maker_pubkey` when set
+    pub order_sig: Option<Binary>,
+    /// Balance of the `access_token` CW20 that admits a caller to public actions, zero disables
+    pub default_access_token_threshold: Uint128,
+    /// Per-escrow access token threshold, falls back to `default_access_token_threshold`
+    pub access_token_threshold: Option<Uint128>,
+    /// Opaque reference for off-chain correlation, at most 256 bytes
+    pub memo: Option<String>,
+    /// Floor for `dst_amount` on source escrows, rejects a misquoted destination leg
+    pub min_dst_amount: Option<Uint128>,
+    /// Accounts that crowdfunded the safety deposit and their shares, summing to `safety_deposit`
+    pub deposit_contributors: Option<Vec<(String, Uint128)>>,
+    /// Check the contract's actual balances before paying out
+    pub check_solvency: bool,
+    /// Prepended to every `method` attribute, defaults to none
+    pub event_prefix: Option<String>,
+    /// Total hours the maker may push back the source cancellation stages, defaults to none
+    pub max_timelock_extension_hours: Option<u8>,
+    /// Seconds withdrawal windows stay open past their end, to absorb block
+    /// time skew at stage boundaries; withdrawals then overlap the start of
+    /// cancellation by that much. Defaults to none, at most 300
+    pub timelock_skew_seconds: Option<u64>,
+}
+
+/// Access token as given at instantiation, validated into `state::AccessToken`
+#[cw_serde]
+pub enum UncheckedAccessToken {
+    Cw20(String),
+    Native(String),
 }
 
 #[cw_serde]
@@ -24,15 +87,53 @@ pub enum ExecuteMsg {
     WithdrawSrc {
         escrow_id: u64,
         secret: String,
+        /// Payout address for the principal, defaults to the taker
+        recipient: Option<String>,
     },
     CancelSrc {
         escrow_id: u64,
     },
     PublicWithdrawSrc {
         escrow_id: u64,
+        /// Public authority signature admitting the caller in place of the access token
+        proof_sig: Option<Binary>,
+        /// Preimage letting the taker self-trigger without the access token
+        secret: Option<String>,
     },
     PublicCancelSrc {
         escrow_id: u64,
+        proof_sig: Option<Binary>,
+    },
+    /// Refund a source escrow to the maker once public cancellation is open and
+    /// the rescue delay has passed, callable by anyone for the capped public caller reward
+    KeeperCancel {
+        escrow_id: u64,
+    },
+    PublicWithdrawSrcAuthorized {
+        escrow_id: u64,
+        authorization_sig: Binary,
+    },
+    CancelSrcSigned {
+        escrow_id: u64,
+        maker_sig: Binary,
+        maker_pubkey: Binary,
+    },
+    /// Push back the source cancellation stages, maker only, before cancellation opens
+    ExtendTimelocks {
+        escrow_id: u64,
+        additional_hours: u8,
+    },
+    /// Publish the secret without withdrawing yet, taker only; funds stay put
+    /// but the escrow can no longer be cancelled
+    RevealSecret {
+        escrow_id: u64,
+        secret: String,
+    },
+    /// Cancel at any time by agreement, submitted by the maker or taker with
+    /// the other party's signature over `mutual_cancel_hash`
+    MutualCancel {
+        escrow_id: u64,
+        counterparty_sig: Binary,
     },
     WithdrawDst {
         escrow_id: u64,
@@ -43,10 +144,64 @@ pub enum ExecuteMsg {
     },
     PublicWithdrawDst {
         escrow_id: u64,
+        proof_sig: Option<Binary>,
+        /// Preimage letting the maker self-trigger without the access token
+        secret: Option<String>,
     },
     Rescue {
         escrow_id: u64,
     },
+    SweepExpired {
+        escrow_id: u64,
+    },
+    /// Add native funds to the principal, depositor only, before cancellation opens
+    TopUp {
+        escrow_id: u64,
+    },
+    // CW20 funding
+    Receive(Cw20ReceiveMsg),
+    // CW721 funding
+    ReceiveNft(Cw721ReceiveMsg),
+    // Admin operations
+    WithdrawFees {
+        recipient: String,
+    },
+    AdminRescue {
+        escrow_id: u64,
+        recipient: String,
+    },
+    SetRescueAllowlist {
+        denoms: Option<Vec<String>>,
+    },
+    /// Nominate a new owner, replacing any pending nomination (owner only)
+    ProposeNewOwner {
+        owner: String,
+    },
+    /// Take over ownership (pending owner only)
+    AcceptOwnership {},
+}
+
+/// Messages embedded in a CW20 `Send` or CW721 `SendNft` to this contract
+#[cw_serde]
+pub enum Cw20HookMsg {
+    /// Fund the CW20 or CW721 principal of the escrow created at instantiation
+    DeployEscrow { escrow_id: u64 },
+    /// Add the sent CW20 tokens to the principal, like `ExecuteMsg::TopUp`
+    TopUp { escrow_id: u64 },
+}
+
+/// CW721 `ReceiveNft` payload, wire-compatible with cw721
+#[cw_serde]
+pub struct Cw721ReceiveMsg {
+    pub sender: String,
+    pub token_id: String,
+    pub msg: Binary,
+}
+
+/// Subset of the CW721 execute interface used to release an NFT principal
+#[cw_serde]
+pub enum Cw721ExecuteMsg {
+    TransferNft { recipient: String, token_id: String },
 }
 
 #[cw_serde]
@@ -54,33 +209,363 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(ConfigResponse)]
     Config {},
+    #[returns(OwnershipResponse)]
+    Ownership {},
+    #[returns(EscrowStateResponse)]
+    EscrowState {},
+    #[returns(ReconciliationResponse)]
+    Reconciliation {},
+    #[returns(TotalValueLockedResponse)]
+    TotalValueLocked {},
+    /// Lifetime counts per final status and cumulative principal volume
+    #[returns(MetricsResponse)]
+    Metrics {},
+    #[returns(EscrowsResponse)]
+    EscrowsByOrderHash { order_hash: String },
+    #[returns(EscrowResponse)]
+    EscrowBySwapId { swap_id: String },
+    #[returns(EscrowsResponse)]
+    EscrowsByMaker {
+        maker: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(EscrowsResponse)]
+    EscrowsByTaker {
+        taker: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Escrows created between the two block heights, inclusive
+    #[returns(EscrowsResponse)]
+    EscrowsByHeight {
+        from_height: u64,
+        to_height: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(ActionPreviewResponse)]
+    ActionPreview { escrow_id: u64, action: EscrowAction },
+    #[returns(AvailableActionsResponse)]
+    AvailableActions { escrow_id: u64 },
+    #[returns(ValidateParamsResponse)]
+    ValidateParams { params: Box<InstantiateMsg> },
+    #[returns(HashlockResponse)]
+    HashlockFor { secret: String },
+    #[returns(CanWithdrawResponse)]
+    CanWithdraw { escrow_id: u64, secret: String },
+    #[returns(ImmutablesHashResponse)]
+    ImmutablesHash { escrow_id: u64 },
+    #[returns(ImmutablesHashResponse)]
+    ComputeImmutablesHash { params: Immutables },
+    #[returns(CompletedEscrowsResponse)]
+    CompletedEscrows {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        status: Option<crate::state::EscrowStatus>,
+    },
+    #[returns(RescueStatusResponse)]
+    RescueStatus { escrow_id: u64 },
+    #[returns(ActionableEscrowsResponse)]
+    ActionableEscrows {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Active escrows whose cancellation stage has started
+    #[returns(PendingCancellationsResponse)]
+    PendingCancellations {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(NearRescueResponse)]
+    NearRescue {
+        within_seconds: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Operator diagnostic listing escrows whose stored state is inconsistent
+    #[returns(InvariantsResponse)]
+    Invariants {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+/// Escrow actions that move funds
+#[cw_serde]
+#[derive(Copy)]
+pub enum EscrowAction {
+    WithdrawSrc,
+    WithdrawDst,
+    CancelSrc,
+    CancelDst,
+    PublicWithdrawSrc,
+    PublicWithdrawDst,
+    PublicCancelSrc,
+    KeeperCancel,
+    Rescue,
+    SweepExpired,
+}
+
+/// What an escrow allows at a given time
+#[cw_serde]
+#[derive(Copy)]
+pub enum EscrowPhase {
+    /// Withdrawal hasn't opened yet
+    Pending,
+    WithdrawOpen,
+    PublicWithdrawOpen,
+    CancelOpen,
+    PublicCancelOpen,
+    RescueOpen,
+    /// Withdrawn, cancelled or rescued
+    Finalized,
+}
+
+/// Who may submit an escrow action
+#[cw_serde]
+#[derive(Copy)]
+pub enum CallerRole {
+    Taker,
+    TakerOrMaker,
+    /// Access token holder, or the taker presenting the secret
+    AccessTokenOrTakerWithSecret,
+    /// Access token holder, or the maker presenting the secret
+    AccessTokenOrMakerWithSecret,
+    AccessToken,
+    Anyone,
 }
 
+/// Every `Config` field, plus contract info
 #[cw_serde]
 pub struct ConfigResponse {
-    pub escrow_id: u64,
-    pub immutables: crate::state::Immutables,
-    pub dst_complement: Option<crate::state::DstImmutablesComplement>,
-    pub escrow_type: crate::state::EscrowType,
-    pub is_active: bool,
-    pub balance: Uint128,
-    pub native_balance: Uint128,
-    pub created_at: String,
+    pub owner: Addr,
+    pub access_token: crate::state::AccessToken,
+    pub rescue_delay: u64,
+    pub factory: Addr,
+    pub creation_fee: Uint128,
+    pub public_caller_reward: Uint128,
+    /// Denom native principals and creation fees must be sent in
+    pub native_denom: String,
+    pub admin_rescue_delay: u64,
+    pub rescue_denom_allowlist: Option<Vec<String>>,
+    pub min_secret_bytes: u8,
+    pub public_authority_pubkey: Option<Binary>,
+    pub withdraw_fee: Uint128,
+    pub treasury: Addr,
+    pub default_access_token_threshold: Uint128,
+    pub check_solvency: bool,
+    pub event_prefix: String,
+    pub max_timelock_extension_hours: u8,
+    pub timelock_skew_seconds: u64,
+    /// cw2 name and version of the deployed code
+    pub contract_version: cw2::ContractVersion,
 }
 
+#[cw_serde]
+pub struct OwnershipResponse {
+    pub owner: Addr,
+    /// Proposed owner that hasn't accepted yet
+    pub pending_owner: Option<Addr>,
+}
+
+/// `EscrowState` returns the same shape as `Escrow`
+pub type EscrowStateResponse = EscrowResponse;
+
 #[cw_serde]
 pub struct EscrowResponse {
     pub escrow_id: u64,
+    pub swap_id: String,
     pub immutables: crate::state::Immutables,
+    pub unpacked_timelocks: crate::state::UnpackedTimelocks,
     pub dst_complement: Option<crate::state::DstImmutablesComplement>,
     pub escrow_type: crate::state::EscrowType,
-    pub is_active: bool,
+    pub status: crate::state::EscrowStatus,
+    /// Phase at query time, derived from status and timelocks
+    pub effective_phase: EscrowPhase,
     pub balance: Uint128,
     pub native_balance: Uint128,
     pub created_at: String,
+    pub created_at_height: u64,
+    pub completed_at_height: Option<u64>,
+    pub ibc_memo: Option<String>,
+    pub safety_deposit_denom: String,
+    pub memo: Option<String>,
+    /// Secret published through `RevealSecret`, if any
+    pub revealed_secret: Option<String>,
+    /// Account that created and funded the escrow
+    pub creator: String,
+    /// Whether the principal has arrived, false for a CW20/CW721 escrow awaiting its transfer
+    pub principal_funded: bool,
 }
 
 #[cw_serde]
 pub struct EscrowsResponse {
     pub escrows: Vec<EscrowResponse>,
-} 
\ No newline at end of file
+}
+
+/// Bookkeeping totals across active escrows, to be compared by operators
+/// against the contract's actual bank/CW20 balances
+#[cw_serde]
+pub struct ReconciliationResponse {
+    pub active_escrows: u64,
+    pub total_balance: Uint128,
+    pub total_native_balance: Uint128,
+}
+
+/// Principal and safety deposit locked by active escrows, per asset
+#[cw_serde]
+pub struct TotalValueLockedResponse {
+    pub assets: Vec<AssetTotal>,
+}
+
+#[cw_serde]
+pub struct MetricsResponse {
+    pub created: u64,
+    pub withdrawn: u64,
+    pub cancelled: u64,
+    pub rescued: u64,
+    pub admin_rescued: u64,
+    pub principal_volume: Vec<AssetTotal>,
+}
+
+#[cw_serde]
+pub struct ActionPreviewResponse {
+    pub action: EscrowAction,
+    /// Recipient of the escrowed principal
+    pub recipient: Addr,
+    pub amount: Uint128,
+    /// Recipient of the safety deposit, `None` means whoever submits the action.
+    /// Refunds of a crowdfunded deposit are split among its contributors instead.
+    pub deposit_recipient: Option<Addr>,
+    pub deposit_amount: Uint128,
+    /// Part of the safety deposit refunded to the creator or its contributors (public cancellation)
+    pub deposit_refund: Uint128,
+    /// Whether the action is currently allowed by escrow type, status and timelocks
+    pub permitted: bool,
+}
+
+#[cw_serde]
+pub struct AvailableAction {
+    pub action: EscrowAction,
+    pub requires_secret: bool,
+    pub caller_role: CallerRole,
+}
+
+#[cw_serde]
+pub struct AvailableActionsResponse {
+    pub escrow_id: u64,
+    /// Actions currently allowed by escrow type, status and timelocks
+    pub actions: Vec<AvailableAction>,
+}
+
+#[cw_serde]
+pub struct CompletedEscrow {
+    pub escrow_id: u64,
+    pub order_hash: String,
+    pub status: crate::state::EscrowStatus,
+    pub completed_at_height: Option<u64>,
+}
+
+#[cw_serde]
+pub struct CompletedEscrowsResponse {
+    pub escrows: Vec<CompletedEscrow>,
+}
+
+/// An escrow a keeper can act on now for a deposit reward
+#[cw_serde]
+pub struct ActionableEscrow {
+    pub escrow_id: u64,
+    pub action: EscrowAction,
+    pub reward: Coin,
+}
+
+#[cw_serde]
+pub struct ActionableEscrowsResponse {
+    pub escrows: Vec<ActionableEscrow>,
+    /// Last escrow id inspected, resume from here when the scan cap was hit
+    pub last_scanned: Option<u64>,
+}
+
+/// An active escrow that is past its cancellation stage
+#[cw_serde]
+pub struct PendingCancellation {
+    pub escrow_id: u64,
+    pub escrow_type: EscrowType,
+    /// When the cancellation stage started (seconds)
+    pub cancellation_at: u64,
+    /// Deposit share paid for `KeeperCancel`, set once any caller may cancel
+    pub keeper_reward: Option<Coin>,
+}
+
+#[cw_serde]
+pub struct PendingCancellationsResponse {
+    pub escrows: Vec<PendingCancellation>,
+    /// Last escrow id inspected, resume from here when the scan cap was hit
+    pub last_scanned: Option<u64>,
+}
+
+/// An escrow failing one or more consistency checks
+#[cw_serde]
+pub struct InvariantViolation {
+    pub escrow_id: u64,
+    pub violations: Vec<String>,
+}
+
+#[cw_serde]
+pub struct InvariantsResponse {
+    pub escrows: Vec<InvariantViolation>,
+    /// Last escrow id inspected, resume from here when the scan cap was hit
+    pub last_scanned: Option<u64>,
+}
+
+/// An active escrow that becomes rescue-eligible within the queried horizon
+#[cw_serde]
+pub struct NearRescueEscrow {
+    pub escrow_id: u64,
+    pub rescue_start: u64,
+    /// Zero once rescue is already available
+    pub seconds_remaining: u64,
+}
+
+#[cw_serde]
+pub struct NearRescueResponse {
+    pub escrows: Vec<NearRescueEscrow>,
+    /// Last escrow id inspected, resume from here when the scan cap was hit
+    pub last_scanned: Option<u64>,
+}
+
+#[cw_serde]
+pub struct RescueStatusResponse {
+    pub rescue_start: u64,
+    pub rescue_available: bool,
+    pub seconds_remaining: u64,
+}
+
+#[cw_serde]
+pub struct HashlockResponse {
+    pub hashlock: String,
+}
+
+/// Dry run of a secret-based withdrawal at the current block time
+#[cw_serde]
+pub struct CanWithdrawResponse {
+    /// The secret is long enough and hashes to the escrow's hashlock
+    pub secret_valid: bool,
+    /// The escrow is active and inside its withdrawal window
+    pub timelock_open: bool,
+    /// Who could submit the withdrawal right now, `None` unless both checks pass
+    pub authorized_caller: Option<String>,
+}
+
+/// Output of `Immutables::hash`, the escrow's duplicate-detection id
+#[cw_serde]
+pub struct ImmutablesHashResponse {
+    pub hash: String,
+}
+
+#[cw_serde]
+pub struct ValidateParamsResponse {
+    pub valid: bool,
+    pub errors: Vec<String>,
+}
@@ -1,8 +1,70 @@
-use cosmwasm_std::{Deps, StdResult};
-use crate::msg::{ConfigResponse};
-use crate::state::{ESCROWS, ESCROW_COUNTER};
+use cosmwasm_std::{Addr, Coin, Deps, Env, Order, StdResult, Uint128};
+use cw_storage_plus::{Bound, Map};
+use crate::contract::get_active_escrow_totals;
+use crate::msg::{
+    ActionPreviewResponse, ActionableEscrow, ActionableEscrowsResponse, AvailableAction, AvailableActionsResponse, CallerRole, CompletedEscrow,
+    CompletedEscrowsResponse, ConfigResponse, OwnershipResponse, EscrowAction, EscrowPhase, EscrowResponse, EscrowStateResponse, EscrowsResponse,
+    CanWithdrawResponse, HashlockResponse, ImmutablesHashResponse, InstantiateMsg, InvariantViolation, InvariantsResponse, NearRescueEscrow, NearRescueResponse, ReconciliationResponse,
+    MetricsResponse, PendingCancellation, PendingCancellationsResponse, RescueStatusResponse, TotalValueLockedResponse, ValidateParamsResponse,
+};
+use crate::execute::validate_escrow_params;
+use crate::state::{Config, EscrowState, EscrowStatus, Immutables, TimelockStage, compute_hashlock, CONFIG, ESCROWS, ESCROW_COUNTER, ORDER_HASH_ESCROWS, SWAP_ID_ESCROWS,
+    MAKER_ESCROWS, TAKER_ESCROWS, PENDING_OWNER, TOTAL_VALUE_LOCKED, METRICS, Metrics};
 
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    // Destructured so a new Config field can't be left out of the response
+    let Config {
+        owner,
+        access_token,
+        rescue_delay,
+        factory,
+        creation_fee,
+        public_caller_reward,
+        native_denom,
+        admin_rescue_delay,
+        rescue_denom_allowlist,
+        min_secret_bytes,
+        public_authority_pubkey,
+        withdraw_fee,
+        treasury,
+        default_access_token_threshold,
+        check_solvency,
+        event_prefix,
+        max_timelock_extension_hours,
+        timelock_skew_seconds,
+    } = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        owner,
+        access_token,
+        rescue_delay,
+        factory,
+        creation_fee,
+        public_caller_reward,
+        native_denom,
+        admin_rescue_delay,
+        rescue_denom_allowlist,
+        min_secret_bytes,
+        public_authority_pubkey,
+        withdraw_fee,
+        treasury,
+        default_access_token_threshold,
+        check_solvency,
+        event_prefix,
+        max_timelock_extension_hours,
+        timelock_skew_seconds,
+        contract_version: cw2::get_contract_version(deps.storage)?,
+    })
+}
+
+pub fn query_ownership(deps: Deps) -> StdResult<OwnershipResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(OwnershipResponse {
+        owner: config.owner,
+        pending_owner: PENDING_OWNER.may_load(deps.storage)?,
+    })
+}
+
+pub fn query_escrow_state(deps: Deps, env: Env) -> StdResult<EscrowStateResponse> {
     // Get the escrow ID (should be 1 since there's only one escrow per contract)
     let escrow_id = ESCROW_COUNTER.load(deps.storage)?;
     
@@ -14,15 +76,685 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
 
     // Load the escrow
     let escrow_state = ESCROWS.load(deps.storage, 1)?;
+    let effective_phase = escrow_phase(deps, &env, &escrow_state)?;
     
-    Ok(ConfigResponse {
-        escrow_id: 1,
+    Ok(escrow_response(1, escrow_state, effective_phase))
+}
+
+pub fn query_reconciliation(deps: Deps) -> StdResult<ReconciliationResponse> {
+    let (active_escrows, total_balance, total_native_balance) = get_active_escrow_totals(deps)?;
+
+    Ok(ReconciliationResponse {
+        active_escrows,
+        total_balance,
+        total_native_balance,
+    })
+}
+
+pub fn query_total_value_locked(deps: Deps) -> StdResult<TotalValueLockedResponse> {
+    let assets = TOTAL_VALUE_LOCKED
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|entry| entry.map(|(_, asset_total)| asset_total))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(TotalValueLockedResponse { assets })
+}
+
+pub fn query_metrics(deps: Deps) -> StdResult<MetricsResponse> {
+    let Metrics { created, withdrawn, cancelled, rescued, admin_rescued, principal_volume } =
+        METRICS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(MetricsResponse { created, withdrawn, cancelled, rescued, admin_rescued, principal_volume })
+}
+
+pub fn query_escrows_by_order_hash(deps: Deps, env: Env, order_hash: String) -> StdResult<EscrowsResponse> {
+    let escrow_ids = ORDER_HASH_ESCROWS.may_load(deps.storage, &order_hash)?.unwrap_or_default();
+
+    let escrows = escrow_ids
+        .into_iter()
+        .map(|escrow_id| {
+            let escrow_state = ESCROWS.load(deps.storage, escrow_id)?;
+            let effective_phase = escrow_phase(deps, &env, &escrow_state)?;
+            Ok(escrow_response(escrow_id, escrow_state, effective_phase))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(EscrowsResponse { escrows })
+}
+
+pub fn query_escrows_by_maker(
+    deps: Deps,
+    env: Env,
+    maker: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EscrowsResponse> {
+    let maker = deps.api.addr_validate(&maker)?;
+    query_escrows_by_party(deps, &env, MAKER_ESCROWS, &maker, start_after, limit)
+}
+
+pub fn query_escrows_by_taker(
+    deps: Deps,
+    env: Env,
+    taker: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EscrowsResponse> {
+    let taker = deps.api.addr_validate(&taker)?;
+    query_escrows_by_party(deps, &env, TAKER_ESCROWS, &taker, start_after, limit)
+}
+
+/// Page through one of the party indexes in escrow id order
+fn query_escrows_by_party(
+    deps: Deps,
+    env: &Env,
+    index: Map<(&Addr, u64), ()>,
+    party: &Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EscrowsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let escrows = index
+        .prefix(party)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|escrow_id| {
+            let escrow_id = escrow_id?;
+            let escrow_state = ESCROWS.load(deps.storage, escrow_id)?;
+            let effective_phase = escrow_phase(deps, env, &escrow_state)?;
+            Ok(escrow_response(escrow_id, escrow_state, effective_phase))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(EscrowsResponse { escrows })
+}
+
+pub fn query_escrows_by_height(
+    deps: Deps,
+    env: Env,
+    from_height: u64,
+    to_height: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EscrowsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let mut escrows = vec![];
+    for result in ESCROWS.range(deps.storage, start, None, Order::Ascending) {
+        let (escrow_id, escrow_state) = result?;
+        let created_at_height = escrow_state.escrow_info.created_at_height;
+        // Ids are assigned in creation order, so nothing later can be back in range
+        if created_at_height > to_height {
+            break;
+        }
+        if created_at_height < from_height {
+            continue;
+        }
+
+        let effective_phase = escrow_phase(deps, &env, &escrow_state)?;
+        escrows.push(escrow_response(escrow_id, escrow_state, effective_phase));
+        if escrows.len() == limit {
+            break;
+        }
+    }
+
+    Ok(EscrowsResponse { escrows })
+}
+
+pub fn query_escrow_by_swap_id(deps: Deps, env: Env, swap_id: String) -> StdResult<EscrowResponse> {
+    let escrow_id = SWAP_ID_ESCROWS.load(deps.storage, &swap_id)?;
+    let escrow_state = ESCROWS.load(deps.storage, escrow_id)?;
+    let effective_phase = escrow_phase(deps, &env, &escrow_state)?;
+    Ok(escrow_response(escrow_id, escrow_state, effective_phase))
+}
+
+/// Dry-run the creation checks for a set of escrow parameters
+pub fn query_validate_params(deps: Deps, env: Env, params: InstantiateMsg) -> StdResult<ValidateParamsResponse> {
+    let errors = validate_escrow_params(deps.api, &env, &params)
+        .iter()
+        .map(|err| err.to_string())
+        .collect::<Vec<_>>();
+
+    Ok(ValidateParamsResponse { valid: errors.is_empty(), errors })
+}
+
+pub fn query_hashlock_for(secret: String) -> StdResult<HashlockResponse> {
+    Ok(HashlockResponse { hashlock: compute_hashlock(&secret) })
+}
+
+pub fn query_can_withdraw(deps: Deps, env: Env, escrow_id: u64, secret: String) -> StdResult<CanWithdrawResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let escrow_state = ESCROWS.load(deps.storage, escrow_id)?;
+    let escrow_info = &escrow_state.escrow_info;
+    let immutables = &escrow_info.immutables;
+
+    let secret_valid = secret.len() >= config.min_secret_bytes as usize
+        && compute_hashlock(&secret) == immutables.hashlock;
+
+    let action = if escrow_info.escrow_type.is_source() {
+        EscrowAction::WithdrawSrc
+    } else {
+        EscrowAction::WithdrawDst
+    };
+    let rescue_delay = escrow_info.effective_rescue_delay(config.rescue_delay);
+    let timelock_open = escrow_info.status.is_active()
+        && is_action_open(&escrow_state, action, env.block.time.seconds(), rescue_delay, config.timelock_skew_seconds);
+
+    // Secret withdrawals are taker-only in both escrow types
+    let authorized_caller = (secret_valid && timelock_open).then(|| immutables.taker.to_string());
+
+    Ok(CanWithdrawResponse { secret_valid, timelock_open, authorized_caller })
+}
+
+pub fn query_immutables_hash(deps: Deps, escrow_id: u64) -> StdResult<ImmutablesHashResponse> {
+    let escrow_state = ESCROWS.load(deps.storage, escrow_id)?;
+    Ok(ImmutablesHashResponse { hash: escrow_state.escrow_info.immutables.hash() })
+}
+
+pub fn query_compute_immutables_hash(params: Immutables) -> StdResult<ImmutablesHashResponse> {
+    Ok(ImmutablesHashResponse { hash: params.hash() })
+}
+
+pub fn query_rescue_status(deps: Deps, env: Env, escrow_id: u64) -> StdResult<RescueStatusResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let escrow_state = ESCROWS.load(deps.storage, escrow_id)?;
+    let escrow_info = &escrow_state.escrow_info;
+
+    let rescue_delay = escrow_info.effective_rescue_delay(config.rescue_delay);
+    let rescue_start = escrow_info.immutables.timelocks.rescue_start(rescue_delay);
+    let current_time = env.block.time.seconds();
+
+    Ok(RescueStatusResponse {
+        rescue_start,
+        rescue_available: escrow_info.immutables.timelocks.is_rescue_available(current_time, rescue_delay),
+        seconds_remaining: rescue_start.saturating_sub(current_time),
+    })
+}
+
+// Pagination defaults
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+// Escrows inspected per ActionableEscrows/NearRescue call, bounds gas when few match
+const MAX_SCAN: usize = 100;
+
+/// Actions that pay the caller a deposit reward, most lucrative first
+const KEEPER_ACTIONS: [EscrowAction; 4] = [
+    EscrowAction::SweepExpired,
+    EscrowAction::PublicCancelSrc,
+    EscrowAction::PublicWithdrawSrc,
+    EscrowAction::PublicWithdrawDst,
+];
+
+pub fn query_actionable_escrows(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ActionableEscrowsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let current_time = env.block.time.seconds();
+
+    let mut escrows = vec![];
+    let mut last_scanned = None;
+    for result in ESCROWS.range(deps.storage, start, None, Order::Ascending).take(MAX_SCAN) {
+        let (escrow_id, escrow_state) = result?;
+        last_scanned = Some(escrow_id);
+
+        let escrow_info = &escrow_state.escrow_info;
+        if !escrow_info.status.is_active() {
+            continue;
+        }
+        let rescue_delay = escrow_info.effective_rescue_delay(config.rescue_delay);
+        let action = KEEPER_ACTIONS
+            .into_iter()
+            .find(|&action| is_action_open(&escrow_state, action, current_time, rescue_delay, config.timelock_skew_seconds));
+
+        if let Some(action) = action {
+            // Public cancellation caps the caller's share of the deposit
+            let reward = match action {
+                EscrowAction::PublicCancelSrc => escrow_state.native_balance.min(config.public_caller_reward),
+                _ => escrow_state.native_balance,
+            };
+            escrows.push(ActionableEscrow {
+                escrow_id,
+                action,
+                reward: Coin { denom: escrow_info.safety_deposit_denom.clone(), amount: reward },
+            });
+            if escrows.len() == limit {
+                break;
+            }
+        }
+    }
+
+    Ok(ActionableEscrowsResponse { escrows, last_scanned })
+}
+
+pub fn query_pending_cancellations(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PendingCancellationsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let current_time = env.block.time.seconds();
+
+    let mut escrows = vec![];
+    let mut last_scanned = None;
+    for result in ESCROWS.range(deps.storage, start, None, Order::Ascending).take(MAX_SCAN) {
+        let (escrow_id, escrow_state) = result?;
+        last_scanned = Some(escrow_id);
+
+        // A revealed secret leaves the escrow to be withdrawn, never cancelled
+        let escrow_info = &escrow_state.escrow_info;
+        if !escrow_info.status.is_active() || escrow_info.revealed_secret.is_some() {
+            continue;
+        }
+        let stage = escrow_info.escrow_type.get_cancellation_stage();
+        let timelocks = &escrow_info.immutables.timelocks;
+        if !timelocks.is_within_stage(current_time, stage) {
+            continue;
+        }
+
+        let rescue_delay = escrow_info.effective_rescue_delay(config.rescue_delay);
+        let keeper_reward = is_action_open(&escrow_state, EscrowAction::KeeperCancel, current_time, rescue_delay, config.timelock_skew_seconds)
+            .then(|| Coin {
+                denom: escrow_info.safety_deposit_denom.clone(),
+                amount: escrow_state.native_balance.min(config.public_caller_reward),
+            });
+        escrows.push(PendingCancellation {
+            escrow_id,
+            escrow_type: escrow_info.escrow_type,
+            cancellation_at: timelocks.get_stage_time(stage),
+            keeper_reward,
+        });
+        if escrows.len() == limit {
+            break;
+        }
+    }
+
+    Ok(PendingCancellationsResponse { escrows, last_scanned })
+}
+
+pub fn query_near_rescue(
+    deps: Deps,
+    env: Env,
+    within_seconds: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<NearRescueResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let current_time = env.block.time.seconds();
+
+    let mut escrows = vec![];
+    let mut last_scanned = None;
+    for result in ESCROWS.range(deps.storage, start, None, Order::Ascending).take(MAX_SCAN) {
+        let (escrow_id, escrow_state) = result?;
+        last_scanned = Some(escrow_id);
+
+        let escrow_info = &escrow_state.escrow_info;
+        if !escrow_info.status.is_active() {
+            continue;
+        }
+        let rescue_delay = escrow_info.effective_rescue_delay(config.rescue_delay);
+        let timelocks = &escrow_info.immutables.timelocks;
+        let rescue_start = timelocks.rescue_start(rescue_delay);
+        let seconds_remaining = if timelocks.is_rescue_available(current_time, rescue_delay) {
+            0
+        } else {
+            rescue_start - current_time
+        };
+
+        if seconds_remaining <= within_seconds {
+            escrows.push(NearRescueEscrow { escrow_id, rescue_start, seconds_remaining });
+            if escrows.len() == limit {
+                break;
+            }
+        }
+    }
+
+    Ok(NearRescueResponse { escrows, last_scanned })
+}
+
+pub fn query_invariants(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<InvariantsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let mut escrows = vec![];
+    let mut last_scanned = None;
+    for result in ESCROWS.range(deps.storage, start, None, Order::Ascending).take(MAX_SCAN) {
+        let (escrow_id, escrow_state) = result?;
+        last_scanned = Some(escrow_id);
+
+        let violations = invariant_violations(deps, escrow_id, &escrow_state)?;
+        if !violations.is_empty() {
+            escrows.push(InvariantViolation { escrow_id, violations });
+            if escrows.len() == limit {
+                break;
+            }
+        }
+    }
+
+    Ok(InvariantsResponse { escrows, last_scanned })
+}
+
+/// Consistency checks every stored escrow should pass, described by the ones it fails
+fn invariant_violations(deps: Deps, escrow_id: u64, escrow_state: &EscrowState) -> StdResult<Vec<String>> {
+    let escrow_info = &escrow_state.escrow_info;
+    let immutables = &escrow_info.immutables;
+    let mut violations = vec![];
+
+    let is_active = escrow_info.status.is_active();
+    if is_active && escrow_state.is_drained() {
+        violations.push("active escrow holds neither principal nor safety deposit".to_string());
+    }
+    match escrow_info.completed_at_height {
+        Some(_) if is_active => violations.push("active escrow has a completion height".to_string()),
+        None if !is_active => violations.push(format!("{:?} escrow has no completion height", escrow_info.status)),
+        Some(height) if height < escrow_info.created_at_height => {
+            violations.push(format!("completed at height {height} before creation at {}", escrow_info.created_at_height));
+        }
+        _ => {}
+    }
+
+    // Lookup indexes are written at creation and never pruned
+    if SWAP_ID_ESCROWS.may_load(deps.storage, &escrow_info.swap_id)? != Some(escrow_id) {
+        violations.push("missing from swap id index".to_string());
+    }
+    let order_hash_ids = ORDER_HASH_ESCROWS.may_load(deps.storage, &immutables.order_hash)?.unwrap_or_default();
+    if !order_hash_ids.contains(&escrow_id) {
+        violations.push("missing from order hash index".to_string());
+    }
+    if !MAKER_ESCROWS.has(deps.storage, (&immutables.maker, escrow_id)) {
+        violations.push("missing from maker index".to_string());
+    }
+    if !TAKER_ESCROWS.has(deps.storage, (&immutables.taker, escrow_id)) {
+        violations.push("missing from taker index".to_string());
+    }
+
+    Ok(violations)
+}
+
+pub fn query_completed_escrows(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    status: Option<EscrowStatus>,
+) -> StdResult<CompletedEscrowsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let escrows = ESCROWS
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|result| match result {
+            Ok((_, escrow_state)) => {
+                let escrow_status = escrow_state.escrow_info.status;
+                !escrow_status.is_active() && status.is_none_or(|status| status == escrow_status)
+            }
+            Err(_) => true,
+        })
+        .take(limit)
+        .map(|result| {
+            let (escrow_id, escrow_state) = result?;
+            Ok(CompletedEscrow {
+                escrow_id,
+                order_hash: escrow_state.escrow_info.immutables.order_hash,
+                status: escrow_state.escrow_info.status,
+                completed_at_height: escrow_state.escrow_info.completed_at_height,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(CompletedEscrowsResponse { escrows })
+}
+
+pub fn query_action_preview(
+    deps: Deps,
+    env: Env,
+    escrow_id: u64,
+    action: EscrowAction,
+) -> StdResult<ActionPreviewResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let escrow_state = ESCROWS.load(deps.storage, escrow_id)?;
+    let escrow_info = &escrow_state.escrow_info;
+    let immutables = &escrow_info.immutables;
+    let escrow_type = escrow_info.escrow_type;
+
+    let recipient = match action {
+        EscrowAction::WithdrawSrc | EscrowAction::WithdrawDst
+        | EscrowAction::PublicWithdrawSrc | EscrowAction::PublicWithdrawDst => {
+            escrow_type.get_withdrawal_recipient(&immutables.maker, &immutables.taker)
+        }
+        EscrowAction::CancelSrc | EscrowAction::CancelDst
+        | EscrowAction::PublicCancelSrc | EscrowAction::KeeperCancel | EscrowAction::SweepExpired => {
+            escrow_type.get_cancellation_recipient(&immutables.maker, &immutables.taker)
+        }
+        // Rescue is taker-only and pays everything to the caller
+        EscrowAction::Rescue => immutables.taker.clone(),
+    };
+
+    let (deposit_recipient, deposit_amount, deposit_refund) = match action {
+        // Rescue is taker-only and pays the deposit to the caller
+        EscrowAction::Rescue => (Some(immutables.taker.clone()), escrow_state.native_balance, Uint128::zero()),
+        // Private cancellation returns the deposit to the creator who posted it
+        EscrowAction::CancelSrc | EscrowAction::CancelDst => {
+            (Some(escrow_info.creator.clone()), escrow_state.native_balance, Uint128::zero())
+        }
+        // The caller takes its capped reward, the remainder is refunded like a private cancellation
+        EscrowAction::PublicCancelSrc | EscrowAction::KeeperCancel => {
+            let caller_reward = escrow_state.native_balance.min(config.public_caller_reward);
+            (None, caller_reward, escrow_state.native_balance - caller_reward)
+        }
+        // The treasury's withdraw fee comes out of the caller's deposit
+        EscrowAction::WithdrawSrc | EscrowAction::WithdrawDst
+        | EscrowAction::PublicWithdrawSrc | EscrowAction::PublicWithdrawDst => {
+            let withdraw_fee = escrow_state.native_balance.min(config.withdraw_fee);
+            (None, escrow_state.native_balance - withdraw_fee, Uint128::zero())
+        }
+        _ => (None, escrow_state.native_balance, Uint128::zero()),
+    };
+
+    let rescue_delay = escrow_info.effective_rescue_delay(config.rescue_delay);
+    let permitted = escrow_info.status.is_active()
+        && is_action_open(&escrow_state, action, env.block.time.seconds(), rescue_delay, config.timelock_skew_seconds);
+
+    Ok(ActionPreviewResponse {
+        action,
+        recipient,
+        amount: escrow_state.balance,
+        deposit_recipient,
+        deposit_amount,
+        deposit_refund,
+        permitted,
+    })
+}
+
+pub fn query_available_actions(
+    deps: Deps,
+    env: Env,
+    escrow_id: u64,
+) -> StdResult<AvailableActionsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let escrow_state = ESCROWS.load(deps.storage, escrow_id)?;
+    let escrow_info = &escrow_state.escrow_info;
+    let current_time = env.block.time.seconds();
+    let rescue_delay = escrow_info.effective_rescue_delay(config.rescue_delay);
+
+    let all_actions = [
+        EscrowAction::WithdrawSrc,
+        EscrowAction::WithdrawDst,
+        EscrowAction::CancelSrc,
+        EscrowAction::CancelDst,
+        EscrowAction::PublicWithdrawSrc,
+        EscrowAction::PublicWithdrawDst,
+        EscrowAction::PublicCancelSrc,
+        EscrowAction::KeeperCancel,
+        EscrowAction::Rescue,
+        EscrowAction::SweepExpired,
+    ];
+
+    let actions = if escrow_info.status.is_active() {
+        all_actions
+            .into_iter()
+            .filter(|&action| is_action_open(&escrow_state, action, current_time, rescue_delay, config.timelock_skew_seconds))
+            .map(|action| AvailableAction {
+                action,
+                requires_secret: matches!(action, EscrowAction::WithdrawSrc | EscrowAction::WithdrawDst),
+                caller_role: caller_role(&escrow_state, action, current_time),
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+
+    Ok(AvailableActionsResponse { escrow_id, actions })
+}
+
+/// Caller requirement for an action (mirrors the execute handlers' authorization checks)
+fn caller_role(escrow_state: &EscrowState, action: EscrowAction, current_time: u64) -> CallerRole {
+    let timelocks = &escrow_state.escrow_info.immutables.timelocks;
+    match action {
+        EscrowAction::WithdrawSrc | EscrowAction::WithdrawDst | EscrowAction::Rescue => CallerRole::Taker,
+        // The funder may cancel too, but isn't a role of its own
+        EscrowAction::CancelDst => CallerRole::TakerOrMaker,
+        // Maker may also cancel a source escrow once public cancellation starts
+        EscrowAction::CancelSrc => {
+            if timelocks.is_within_stage(current_time, TimelockStage::SrcPublicCancellation) {
+                CallerRole::TakerOrMaker
+            } else {
+                CallerRole::Taker
+            }
+        }
+        EscrowAction::PublicWithdrawSrc => CallerRole::AccessTokenOrTakerWithSecret,
+        EscrowAction::PublicWithdrawDst => CallerRole::AccessTokenOrMakerWithSecret,
+        EscrowAction::PublicCancelSrc => CallerRole::AccessToken,
+        EscrowAction::KeeperCancel | EscrowAction::SweepExpired => CallerRole::Anyone,
+    }
+}
+
+/// Check escrow type and timelocks for an action (mirrors the execute handlers)
+fn is_action_open(
+    escrow_state: &EscrowState,
+    action: EscrowAction,
+    current_time: u64,
+    rescue_delay: u64,
+    skew: u64,
+) -> bool {
+    let escrow_type = escrow_state.escrow_info.escrow_type;
+    let timelocks = &escrow_state.escrow_info.immutables.timelocks;
+    let is_withdrawal = matches!(
+        action,
+        EscrowAction::WithdrawSrc | EscrowAction::WithdrawDst
+            | EscrowAction::PublicWithdrawSrc | EscrowAction::PublicWithdrawDst
+    );
+    if is_withdrawal
+        && (!escrow_state.escrow_info.principal_funded || escrow_state.escrow_info.is_past_withdrawal_deadline(current_time))
+    {
+        return false;
+    }
+    // A revealed secret rules out every refund, see ensure_secret_not_revealed
+    let is_cancellation = matches!(
+        action,
+        EscrowAction::CancelSrc | EscrowAction::CancelDst
+            | EscrowAction::PublicCancelSrc | EscrowAction::KeeperCancel | EscrowAction::SweepExpired
+    );
+    if is_cancellation && escrow_state.escrow_info.revealed_secret.is_some() {
+        return false;
+    }
+    match action {
+        EscrowAction::WithdrawSrc => escrow_type.is_source()
+            && (timelocks.is_within_window_with_grace(current_time, TimelockStage::SrcWithdrawal, skew)
+                || timelocks.is_within_window_with_grace(current_time, TimelockStage::SrcPublicWithdrawal, skew)),
+        EscrowAction::WithdrawDst => escrow_type.is_destination()
+            && (timelocks.is_within_window_with_grace(current_time, TimelockStage::DstWithdrawal, skew)
+                || timelocks.is_within_window_with_grace(current_time, TimelockStage::DstPublicWithdrawal, skew)),
+        EscrowAction::CancelSrc => escrow_type.is_source()
+            && timelocks.is_within_stage(current_time, TimelockStage::SrcCancellation),
+        EscrowAction::CancelDst => escrow_type.is_destination()
+            && timelocks.is_within_stage(current_time, TimelockStage::DstCancellation),
+        EscrowAction::PublicWithdrawSrc => escrow_type.is_source()
+            && timelocks.is_within_window_with_grace(current_time, TimelockStage::SrcPublicWithdrawal, skew),
+        EscrowAction::PublicWithdrawDst => escrow_type.is_destination()
+            && timelocks.is_within_window_with_grace(current_time, TimelockStage::DstPublicWithdrawal, skew),
+        EscrowAction::PublicCancelSrc => escrow_type.is_source()
+            && timelocks.is_within_stage(current_time, TimelockStage::SrcPublicCancellation),
+        EscrowAction::KeeperCancel => escrow_type.is_source()
+            && timelocks.is_within_stage(current_time, TimelockStage::SrcPublicCancellation)
+            && timelocks.is_rescue_available(current_time, rescue_delay),
+        EscrowAction::Rescue => timelocks.is_rescue_available(current_time, rescue_delay),
+        EscrowAction::SweepExpired => {
+            let final_stage = escrow_type.get_public_cancellation_stage()
+                .unwrap_or_else(|| escrow_type.get_cancellation_stage());
+            timelocks.is_within_stage(current_time, final_stage)
+                && timelocks.is_rescue_available(current_time, rescue_delay)
+        }
+    }
+}
+
+fn escrow_response(escrow_id: u64, escrow_state: EscrowState, effective_phase: EscrowPhase) -> EscrowResponse {
+    EscrowResponse {
+        escrow_id,
+        swap_id: escrow_state.escrow_info.swap_id,
+        unpacked_timelocks: escrow_state.escrow_info.immutables.timelocks.unpack(),
         immutables: escrow_state.escrow_info.immutables,
         dst_complement: escrow_state.escrow_info.dst_complement,
         escrow_type: escrow_state.escrow_info.escrow_type,
-        is_active: escrow_state.escrow_info.is_active,
+        status: escrow_state.escrow_info.status,
+        effective_phase,
         balance: escrow_state.balance,
         native_balance: escrow_state.native_balance,
         created_at: escrow_state.escrow_info.created_at.to_string(),
-    })
-} 
\ No newline at end of file
+        created_at_height: escrow_state.escrow_info.created_at_height,
+        completed_at_height: escrow_state.escrow_info.completed_at_height,
+        ibc_memo: escrow_state.escrow_info.ibc_memo,
+        safety_deposit_denom: escrow_state.escrow_info.safety_deposit_denom,
+        memo: escrow_state.escrow_info.memo,
+        revealed_secret: escrow_state.escrow_info.revealed_secret,
+        creator: escrow_state.escrow_info.creator.to_string(),
+        principal_funded: escrow_state.escrow_info.principal_funded,
+    }
+}
+
+/// Phase of an escrow at the current block time. Rescue takes precedence once
+/// available, otherwise the latest timelock stage that has started applies.
+fn escrow_phase(deps: Deps, env: &Env, escrow_state: &EscrowState) -> StdResult<EscrowPhase> {
+    let escrow_info = &escrow_state.escrow_info;
+    if !escrow_info.status.is_active() {
+        return Ok(EscrowPhase::Finalized);
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let current_time = env.block.time.seconds();
+    let timelocks = &escrow_info.immutables.timelocks;
+    let escrow_type = escrow_info.escrow_type;
+
+    let phase = if timelocks.is_rescue_available(current_time, escrow_info.effective_rescue_delay(config.rescue_delay)) {
+        EscrowPhase::RescueOpen
+    } else if escrow_type
+        .get_public_cancellation_stage()
+        .is_some_and(|stage| timelocks.is_within_stage(current_time, stage))
+    {
+        EscrowPhase::PublicCancelOpen
+    } else if timelocks.is_within_stage(current_time, escrow_type.get_cancellation_stage()) {
+        EscrowPhase::CancelOpen
+    } else if timelocks.is_within_stage(current_time, escrow_type.get_public_withdrawal_stage()) {
+        EscrowPhase::PublicWithdrawOpen
+    } else if timelocks.is_within_stage(current_time, escrow_type.get_withdrawal_stage()) {
+        EscrowPhase::WithdrawOpen
+    } else {
+        EscrowPhase::Pending
+    };
+
+    Ok(phase)
+}
@@ -1,17 +1,64 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128, Timestamp, StdResult, StdError};
+use cosmwasm_std::{Addr, Binary, Uint128, Timestamp, StdResult, StdError, Storage};
 use cw_storage_plus::{Item, Map};
 use sha2::{Sha256, Digest};
 
+use crate::error::ContractError;
+
+/// Default denom for native principal, safety deposits and fees
+pub const NATIVE_DENOM: &str = "uatom";
+
+/// Minimum time from deployment to the last timelock stage of an escrow
+pub const MIN_TIMELOCK_SPAN: u64 = 3 * 3600;
+
+/// Default minimum secret length, short preimages can be brute-forced from the hashlock
+pub const DEFAULT_MIN_SECRET_BYTES: u8 = 32;
+
+/// Maximum length of an escrow's off-chain correlation memo
+pub const MAX_MEMO_BYTES: usize = 256;
+
+/// Upper bound on `timelock_skew_seconds`, well below the one-hour stage granularity
+pub const MAX_TIMELOCK_SKEW_SECONDS: u64 = 300;
+
+// Floors and caps below are fixed in code rather than taken from the creator's
+// InstantiateMsg, which could otherwise opt out of them
+
+/// Minimum rescue delay, default or per-escrow override
+pub const MIN_RESCUE_DELAY: u64 = 3600;
+
+/// Minimum safety deposit in basis points of the principal
+pub const MIN_SAFETY_DEPOSIT_BPS: u16 = 100;
+
+/// Minimum seconds from deployment before either cancellation stage may start
+pub const MIN_CANCELLATION_OFFSET: u64 = 3 * 3600;
+
+/// Cap on the final timelock stage plus the rescue delay
+pub const MAX_LIFETIME_SECONDS: u64 = 30 * 86400;
+
 #[cw_serde]
 pub struct Config {
     pub owner: Addr,
-    pub access_token: Addr,
+    pub access_token: AccessToken,
     pub rescue_delay: u64,
     pub factory: Addr,
+    pub creation_fee: Uint128, // Paid by the creator on top of the escrowed funds, withdrawable by the owner
+    pub public_caller_reward: Uint128, // Cap on the deposit paid to public cancellers
+    pub native_denom: String, // Denom for the native principal, deposit and fees
+    pub admin_rescue_delay: u64, // Owner-only emergency rescue, longer than any rescue delay
+    pub rescue_denom_allowlist: Option<Vec<String>>, // Extra denoms a rescue may forward; None forwards all
+    pub min_secret_bytes: u8, // Minimum length of a revealed secret
+    pub public_authority_pubkey: Option<Binary>, // Signs public-action eligibility proofs
+    pub withdraw_fee: Uint128, // Taken from the safety deposit on withdrawal, capped at the deposit
+    pub treasury: Addr, // Receives withdraw fees
+    pub default_access_token_threshold: Uint128, // Balance of access_token admitting public callers; zero disables
+    pub check_solvency: bool, // Query actual balances before paying out, see ensure_solvent
+    pub event_prefix: String, // Prepended to `method` attributes, empty for none
+    pub max_timelock_extension_hours: u8, // Total hours ExtendTimelocks may add per escrow; zero disables
+    pub timelock_skew_seconds: u64, // Grace past the end of withdrawal windows, overlapping the start of cancellation
 }
 
-/// Escrow type to differentiate source vs destination behavior
+/// Escrow type to differentiate source vs destination behavior.
+/// Not `cw_serde`: that would rename the variants to snake_case on the wire.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub enum EscrowType {
     Source,     // EscrowSrc behavior
@@ -77,6 +124,14 @@ impl EscrowType {
         }
     }
 
+    /// Get the last stage of the timelock progression for this escrow type
+    pub fn get_final_stage(&self) -> TimelockStage {
+        match self {
+            EscrowType::Source => TimelockStage::SrcPublicCancellation,
+            EscrowType::Destination => TimelockStage::DstCancellation,
+        }
+    }
+
     /// Check if this escrow type supports public cancellation
     pub fn supports_public_cancellation(&self) -> bool {
         self.get_public_cancellation_stage().is_some()
@@ -170,6 +225,19 @@ pub struct PackedTimelocks {
     pub destination_data: u64,
 }
 
+/// Human-readable timelocks: offsets are hours after `deployed_at`
+#[cw_serde]
+pub struct UnpackedTimelocks {
+    pub deployed_at: u32,
+    pub src_withdrawal: u8,
+    pub src_public_withdrawal: u8,
+    pub src_cancellation: u8,
+    pub src_public_cancellation: u8,
+    pub dst_withdrawal: u8,
+    pub dst_public_withdrawal: u8,
+    pub dst_cancellation: u8,
+}
+
 impl PackedTimelocks {
     // Bit masks and offsets
     const DEPLOYED_AT_MASK: u64 = 0xFFFFFFFF; // 32 bits
@@ -178,6 +246,7 @@ impl PackedTimelocks {
     const DEPLOYED_AT_OFFSET: u64 = 32;
 
     /// Create packed timelocks from individual values
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         deployed_at: u32,
         src_withdrawal: u8,
@@ -207,7 +276,8 @@ impl PackedTimelocks {
         }
     }
 
-    /// Get deployed_at timestamp
+    /// Get deployed_at timestamp. Stored as 32-bit unix seconds, so escrows
+    /// can't be created after 2106-02-07; creation rejects later block times.
     pub fn deployed_at(&self) -> u32 {
         (self.source_data & Self::DEPLOYED_AT_MASK) as u32
     }
@@ -239,6 +309,20 @@ impl PackedTimelocks {
         }
     }
 
+    /// Unpack all offsets into named fields
+    pub fn unpack(&self) -> UnpackedTimelocks {
+        UnpackedTimelocks {
+            deployed_at: self.deployed_at(),
+            src_withdrawal: self.get(TimelockStage::SrcWithdrawal),
+            src_public_withdrawal: self.get(TimelockStage::SrcPublicWithdrawal),
+            src_cancellation: self.get(TimelockStage::SrcCancellation),
+            src_public_cancellation: self.get(TimelockStage::SrcPublicCancellation),
+            dst_withdrawal: self.get(TimelockStage::DstWithdrawal),
+            dst_public_withdrawal: self.get(TimelockStage::DstPublicWithdrawal),
+            dst_cancellation: self.get(TimelockStage::DstCancellation),
+        }
+    }
+
     /// Get stage time in seconds (converts hours to seconds)
     pub fn get_stage_time(&self, stage: TimelockStage) -> u64 {
         let hours = self.get(stage) as u64;
@@ -246,12 +330,45 @@ impl PackedTimelocks {
         deployed_at + (hours * 3600) // Convert hours to seconds
     }
 
-    /// Check if current time is within a specific stage
+    /// Check if a specific stage has started (see `is_within_window` for the bounded check)
     pub fn is_within_stage(&self, current_time: u64, stage: TimelockStage) -> bool {
         let stage_time = self.get_stage_time(stage);
         current_time >= stage_time
     }
 
+    /// Get the window `[start, end)` of a stage, where the window ends when the
+    /// next stage of the same chain starts. The final stage of each chain is open-ended.
+    pub fn stage_window(&self, stage: TimelockStage) -> (u64, Option<u64>) {
+        let next_stage = match stage {
+            TimelockStage::SrcWithdrawal => Some(TimelockStage::SrcPublicWithdrawal),
+            TimelockStage::SrcPublicWithdrawal => Some(TimelockStage::SrcCancellation),
+            TimelockStage::SrcCancellation => Some(TimelockStage::SrcPublicCancellation),
+            TimelockStage::SrcPublicCancellation => None,
+            TimelockStage::DstWithdrawal => Some(TimelockStage::DstPublicWithdrawal),
+            TimelockStage::DstPublicWithdrawal => Some(TimelockStage::DstCancellation),
+            TimelockStage::DstCancellation => None,
+        };
+
+        (
+            self.get_stage_time(stage),
+            next_stage.map(|next| self.get_stage_time(next)),
+        )
+    }
+
+    /// Check if current time falls inside a stage's window (start inclusive, end exclusive)
+    pub fn is_within_window(&self, current_time: u64, stage: TimelockStage) -> bool {
+        self.is_within_window_with_grace(current_time, stage, 0)
+    }
+
+    /// Like `is_within_window`, but keep the window open `grace` seconds past
+    /// its end to absorb block time skew. The start is never moved: for that
+    /// long the window overlaps the next stage, e.g. a withdrawal by secret can
+    /// still land after cancellation has opened.
+    pub fn is_within_window_with_grace(&self, current_time: u64, stage: TimelockStage, grace: u64) -> bool {
+        let (start, end) = self.stage_window(stage);
+        current_time >= start && end.is_none_or(|end| current_time < end.saturating_add(grace))
+    }
+
     /// Check if a stage has passed (current time > stage time)
     pub fn has_stage_passed(&self, current_time: u64, stage: TimelockStage) -> bool {
         let stage_time = self.get_stage_time(stage);
@@ -285,6 +402,22 @@ impl PackedTimelocks {
         current_time >= rescue_start
     }
 
+    /// Push both source cancellation stages back by `hours`, leaving the
+    /// withdrawal stages alone. `None` if an offset would overflow.
+    pub fn extend_src_cancellation(&self, hours: u8) -> Option<Self> {
+        let extended = |stage| self.get(stage).checked_add(hours);
+        Some(Self::new(
+            self.deployed_at(),
+            self.get(TimelockStage::SrcWithdrawal),
+            self.get(TimelockStage::SrcPublicWithdrawal),
+            extended(TimelockStage::SrcCancellation)?,
+            extended(TimelockStage::SrcPublicCancellation)?,
+            self.get(TimelockStage::DstWithdrawal),
+            self.get(TimelockStage::DstPublicWithdrawal),
+            self.get(TimelockStage::DstCancellation),
+        ))
+    }
+
     /// Validate timelock values (ensure logical progression)
     pub fn validate(&self) -> StdResult<()> {
         let deployed_at = self.deployed_at();
@@ -339,6 +472,53 @@ impl PackedTimelocks {
     }
 }
 
+/// Asset whose balance admits public callers
+#[cw_serde]
+pub enum AccessToken {
+    /// CW20 token contract, whose own address also counts as a holder
+    Cw20(Addr),
+    /// Bank coin, by denom
+    Native(String),
+}
+
+/// Asset locked as the escrow principal
+#[cw_serde]
+pub enum AssetKind {
+    /// Bank coin, by denom
+    Native(String),
+    /// CW20 token contract
+    Cw20(Addr),
+    /// A single NFT of a CW721 contract
+    Cw721 { contract: Addr, token_id: String },
+}
+
+impl AssetKind {
+    /// Denom or contract address
+    pub fn as_str(&self) -> &str {
+        match self {
+            AssetKind::Native(denom) => denom,
+            AssetKind::Cw20(token) => token.as_str(),
+            AssetKind::Cw721 { contract, .. } => contract.as_str(),
+        }
+    }
+
+    /// Storage key of the asset's value-locked total
+    pub fn key(&self) -> String {
+        match self {
+            AssetKind::Native(denom) => format!("native:{denom}"),
+            AssetKind::Cw20(token) => format!("cw20:{token}"),
+            AssetKind::Cw721 { contract, token_id } => format!("cw721:{contract}:{token_id}"),
+        }
+    }
+}
+
+/// Amount of one asset locked across active escrows
+#[cw_serde]
+pub struct AssetTotal {
+    pub asset: AssetKind,
+    pub total: Uint128,
+}
+
 /// Core immutables structure (matches Solidity IBaseEscrow.Immutable)
 #[cw_serde]
 pub struct Immutables {
@@ -346,12 +526,92 @@ pub struct Immutables {
     pub hashlock: String,        // bytes32 equivalent (hash of secret)
     pub maker: Addr,             // Address equivalent
     pub taker: Addr,             // Address equivalent
-    pub token: Addr,             // Address equivalent
+    pub token: AssetKind,        // Native denom, CW20 contract or CW721 NFT
     pub amount: Uint128,         // uint256 equivalent
     pub safety_deposit: Uint128, // uint256 equivalent
     pub timelocks: PackedTimelocks, // Packed timelocks
 }
 
+/// Hashlock for a secret: hex-encoded SHA256 of the secret's UTF-8 bytes
+pub fn compute_hashlock(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+/// Correlation id of a cross-chain swap: sha256(order_hash || hashlock) as hex.
+/// Unlike escrow ids it is the same for the source and destination escrows.
+pub fn compute_swap_id(order_hash: &str, hashlock: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(order_hash.as_bytes());
+    hasher.update(hashlock.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Message hash a maker signs to authorize `CancelSrcSigned`.
+/// The contract address is included so a signature can't be replayed on
+/// another escrow contract for the same order.
+pub fn cancel_authorization_hash(contract: &Addr, escrow_id: u64, order_hash: &str) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(contract.as_bytes());
+    hasher.update(escrow_id.to_be_bytes());
+    hasher.update(order_hash.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+/// Message hash a maker signs to commit an order to its hashlock and principal,
+/// so an escrow can't be created with terms the maker never agreed to. The
+/// escrow contract doesn't exist yet when the order is signed, so unlike the
+/// other authorizations this one isn't bound to a contract address.
+pub fn order_commitment_hash(order_hash: &str, hashlock: &str, amount: Uint128, token: &str) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(b"order_commitment");
+    hasher.update(order_hash.as_bytes());
+    hasher.update(hashlock.as_bytes());
+    hasher.update(amount.u128().to_be_bytes());
+    hasher.update(token.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+/// Message hash a maker or taker signs to let `relayer` run
+/// `PublicWithdrawSrcAuthorized` without the access token.
+pub fn public_withdraw_authorization_hash(contract: &Addr, escrow_id: u64, relayer: &Addr) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(contract.as_bytes());
+    hasher.update(escrow_id.to_be_bytes());
+    hasher.update(relayer.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+/// Message hash the public authority signs to make `caller` eligible for the
+/// public actions of an escrow, domain-separated from the party signatures.
+pub fn public_authority_proof_hash(contract: &Addr, escrow_id: u64, caller: &Addr) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(b"public_authority");
+    hasher.update(contract.as_bytes());
+    hasher.update(escrow_id.to_be_bytes());
+    hasher.update(caller.as_bytes());
+    hasher.finalize().to_vec()
+}
+
+/// Message hash a maker or taker signs to agree to `MutualCancel`, submitted
+/// by the other party. Domain-separated so no other party signature qualifies.
+pub fn mutual_cancel_hash(contract: &Addr, escrow_id: u64) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(b"mutual_cancel");
+    hasher.update(contract.as_bytes());
+    hasher.update(escrow_id.to_be_bytes());
+    hasher.finalize().to_vec()
+}
+
+/// Ensure a 32-byte hash is encoded as exactly 64 lowercase hex characters
+fn validate_hash32(name: &str, value: &str) -> StdResult<()> {
+    if value.len() != 64 || !value.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)) {
+        return Err(StdError::generic_err(format!(
+            "{name} must be 64 lowercase hex characters, got {value:?}"
+        )));
+    }
+    Ok(())
+}
+
 impl Immutables {
     /// Generate deterministic hash (equivalent to Solidity's keccak256)
     pub fn hash(&self) -> String {
@@ -361,6 +621,9 @@ impl Immutables {
         hasher.update(self.maker.as_str().as_bytes());
         hasher.update(self.taker.as_str().as_bytes());
         hasher.update(self.token.as_str().as_bytes());
+        if let AssetKind::Cw721 { token_id, .. } = &self.token {
+            hasher.update(token_id.as_bytes());
+        }
         hasher.update(self.amount.to_string().as_bytes());
         hasher.update(self.safety_deposit.to_string().as_bytes());
         hasher.update(self.timelocks.source_data.to_string().as_bytes());
@@ -371,12 +634,8 @@ impl Immutables {
 
     /// Validate immutables structure
     pub fn validate(&self) -> StdResult<()> {
-        if self.order_hash.is_empty() {
-            return Err(StdError::generic_err("Order hash cannot be empty"));
-        }
-        if self.hashlock.is_empty() {
-            return Err(StdError::generic_err("Hashlock cannot be empty"));
-        }
+        validate_hash32("Order hash", &self.order_hash)?;
+        validate_hash32("Hashlock", &self.hashlock)?;
         if self.amount == Uint128::zero() {
             return Err(StdError::generic_err("Amount cannot be zero"));
         }
@@ -421,14 +680,67 @@ pub struct DstImmutablesComplement {
     pub chain_id: String,
 }
 
+/// Escrow lifecycle status
+#[cw_serde]
+#[derive(Copy)]
+pub enum EscrowStatus {
+    Active,
+    Withdrawn,
+    Cancelled,
+    Rescued,
+    AdminRescued,
+}
+
+impl EscrowStatus {
+    /// Check if escrow can still be acted upon
+    pub fn is_active(&self) -> bool {
+        matches!(self, EscrowStatus::Active)
+    }
+}
+
 /// Escrow information structure
 #[cw_serde]
 pub struct EscrowInfo {
     pub immutables: Immutables,
     pub dst_complement: Option<DstImmutablesComplement>,
     pub escrow_type: EscrowType, // Source or Destination
-    pub is_active: bool,
+    pub status: EscrowStatus,
     pub created_at: Timestamp,
+    pub created_at_height: u64,
+    pub completed_at_height: Option<u64>, // Set once the escrow reaches a terminal status
+    pub rescue_delay: Option<u64>, // Overrides config.rescue_delay when set
+    pub ibc_memo: Option<String>, // Routing info for a follow-up IBC transfer
+    pub safety_deposit_denom: String, // Denom of native_balance
+    pub maker_pubkey: Option<Binary>, // Authorizes CancelSrcSigned and PublicWithdrawSrcAuthorized
+    pub taker_pubkey: Option<Binary>, // Authorizes PublicWithdrawSrcAuthorized
+    pub swap_id: String, // Shared by both legs of a swap, see compute_swap_id
+    pub withdrawal_deadline: Option<u8>, // Hours after deployment past which withdrawals are rejected
+    pub access_token_threshold: Option<Uint128>, // Overrides config.default_access_token_threshold when set
+    pub memo: Option<String>, // Opaque integrator reference, never interpreted
+    pub deposit_contributors: Vec<(Addr, Uint128)>, // Crowdfunded deposit shares, refunded pro rata; empty when the creator posted it
+    pub revealed_secret: Option<String>, // Published by RevealSecret ahead of the withdrawal
+    pub creator: Addr, // Account that instantiated and funded the escrow, possibly a factory or relayer
+    pub timelock_extension_hours: u8, // Hours added to the cancellation stages through ExtendTimelocks so far
+    pub principal_funded: bool, // Native principals arrive at instantiation, CW20/CW721 ones through their receive hook
+}
+
+impl EscrowInfo {
+    /// Get the rescue delay for this escrow, preferring the per-escrow override
+    pub fn effective_rescue_delay(&self, default_delay: u64) -> u64 {
+        self.rescue_delay.unwrap_or(default_delay)
+    }
+
+    /// Get the access token balance public callers need, preferring the per-escrow override
+    pub fn effective_access_token_threshold(&self, default_threshold: Uint128) -> Uint128 {
+        self.access_token_threshold.unwrap_or(default_threshold)
+    }
+
+    /// Check if the optional hard withdrawal deadline has passed
+    pub fn is_past_withdrawal_deadline(&self, current_time: u64) -> bool {
+        let deployed_at = self.immutables.timelocks.deployed_at() as u64;
+        self.withdrawal_deadline
+            .is_some_and(|hours| current_time > deployed_at + hours as u64 * 3600)
+    }
 }
 
 /// Complete escrow state
@@ -439,15 +751,154 @@ pub struct EscrowState {
     pub native_balance: Uint128,
 }
 
+impl EscrowState {
+    /// Whether neither principal nor safety deposit is left to pay out
+    pub fn is_drained(&self) -> bool {
+        self.balance.is_zero() && self.native_balance.is_zero()
+    }
+
+    /// Reduce the principal balance, erroring instead of underflowing
+    pub fn debit_principal(&mut self, amount: Uint128) -> Result<(), ContractError> {
+        self.balance = debit(self.balance, amount)?;
+        Ok(())
+    }
+
+    /// Reduce the safety deposit balance, erroring instead of underflowing
+    pub fn debit_deposit(&mut self, amount: Uint128) -> Result<(), ContractError> {
+        self.native_balance = debit(self.native_balance, amount)?;
+        Ok(())
+    }
+}
+
+fn debit(available: Uint128, amount: Uint128) -> Result<Uint128, ContractError> {
+    available.checked_sub(amount).map_err(|_| ContractError::InsufficientBalance { 
+        required: amount.to_string(), 
+        available: available.to_string() 
+    })
+}
+
 // Storage keys
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const ESCROWS: Map<u64, EscrowState> = Map::new("escrows");
 pub const ESCROW_COUNTER: Item<u64> = Item::new("escrow_counter");
+pub const COLLECTED_FEES: Item<Uint128> = Item::new("collected_fees");
+pub const WITHDRAW_FEES_PAID: Item<Uint128> = Item::new("withdraw_fees_paid");
+// Owner proposed by `ProposeNewOwner`, promoted once it calls `AcceptOwnership`
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
+pub const ORDER_HASH_ESCROWS: Map<&str, Vec<u64>> = Map::new("order_hash_escrows");
+pub const SWAP_ID_ESCROWS: Map<&str, u64> = Map::new("swap_id_escrows");
+// Party indexes, kept after finalization so they list a party's full history
+pub const MAKER_ESCROWS: Map<(&Addr, u64), ()> = Map::new("maker_escrows");
+pub const TAKER_ESCROWS: Map<(&Addr, u64), ()> = Map::new("taker_escrows");
+/// Store a new escrow, refusing to overwrite an id that is already taken.
+/// Ids only ever increase, so a collision means a bug in whatever picked the id.
+pub fn create_escrow(storage: &mut dyn Storage, escrow_id: u64, escrow_state: &EscrowState) -> Result<(), ContractError> {
+    ESCROWS.update(storage, escrow_id, |existing| match existing {
+        Some(_) => Err(ContractError::EscrowAlreadyExists { hash: escrow_state.escrow_info.swap_id.clone() }),
+        None => Ok(escrow_state.clone()),
+    })?;
+    Ok(())
+}
+
+/// Lifetime lifecycle counters, kept up to date by `lock_value` and `release_value`
+/// so analytics don't need to scan escrows
+#[cw_serde]
+#[derive(Default)]
+pub struct Metrics {
+    pub created: u64,
+    pub withdrawn: u64,
+    pub cancelled: u64,
+    pub rescued: u64,
+    pub admin_rescued: u64,
+    pub principal_volume: Vec<AssetTotal>, // Principal ever escrowed per asset, top-ups included
+}
+
+pub const METRICS: Item<Metrics> = Item::new("metrics");
+
+/// Add escrowed principal to the cumulative volume, e.g. on a top-up
+pub fn record_principal_volume(storage: &mut dyn Storage, asset: AssetKind, amount: Uint128) -> StdResult<()> {
+    let mut metrics = METRICS.may_load(storage)?.unwrap_or_default();
+    match metrics.principal_volume.iter_mut().find(|entry| entry.asset == asset) {
+        Some(entry) => entry.total = entry.total.checked_add(amount)?,
+        None => metrics.principal_volume.push(AssetTotal { asset, total: amount }),
+    }
+    METRICS.save(storage, &metrics)
+}
+
+// Running per-asset totals of active escrows, keyed by `AssetKind::key`
+pub const TOTAL_VALUE_LOCKED: Map<&str, AssetTotal> = Map::new("total_value_locked");
+
+/// Count a new escrow's principal and safety deposit towards the locked totals
+/// and the lifetime metrics
+pub fn lock_value(storage: &mut dyn Storage, escrow_state: &EscrowState) -> StdResult<()> {
+    for (asset, amount) in locked_assets(escrow_state) {
+        add_locked_value(storage, asset, amount)?;
+    }
+
+    let mut metrics = METRICS.may_load(storage)?.unwrap_or_default();
+    metrics.created += 1;
+    METRICS.save(storage, &metrics)?;
+    // A token principal not delivered yet is recorded by its receive hook
+    if escrow_state.balance.is_zero() {
+        return Ok(());
+    }
+    record_principal_volume(storage, escrow_state.escrow_info.immutables.token.clone(), escrow_state.balance)
+}
+
+/// Add to the locked total of a single asset, e.g. a principal top-up
+pub fn add_locked_value(storage: &mut dyn Storage, asset: AssetKind, amount: Uint128) -> StdResult<()> {
+    TOTAL_VALUE_LOCKED.update(storage, &asset.key(), |entry| -> StdResult<_> {
+        let mut entry = entry.unwrap_or(AssetTotal { asset, total: Uint128::zero() });
+        entry.total = entry.total.checked_add(amount)?;
+        Ok(entry)
+    })?;
+    Ok(())
+}
+
+/// Remove a finalized escrow's principal and safety deposit from the locked totals
+/// and count its final status
+pub fn release_value(storage: &mut dyn Storage, escrow_state: &EscrowState) -> StdResult<()> {
+    for (asset, amount) in locked_assets(escrow_state) {
+        let key = asset.key();
+        let mut entry = TOTAL_VALUE_LOCKED.load(storage, &key)?;
+        entry.total = entry.total.checked_sub(amount)?;
+        if entry.total.is_zero() {
+            TOTAL_VALUE_LOCKED.remove(storage, &key);
+        } else {
+            TOTAL_VALUE_LOCKED.save(storage, &key, &entry)?;
+        }
+    }
+
+    let mut metrics = METRICS.may_load(storage)?.unwrap_or_default();
+    match escrow_state.escrow_info.status {
+        EscrowStatus::Withdrawn => metrics.withdrawn += 1,
+        EscrowStatus::Cancelled => metrics.cancelled += 1,
+        EscrowStatus::Rescued => metrics.rescued += 1,
+        EscrowStatus::AdminRescued => metrics.admin_rescued += 1,
+        EscrowStatus::Active => {}
+    }
+    METRICS.save(storage, &metrics)
+}
+
+/// The principal balance only grows (top-ups) while the escrow is active, but
+/// the deposit is debited as it is paid out, so count the deposit as created
+fn locked_assets(escrow_state: &EscrowState) -> Vec<(AssetKind, Uint128)> {
+    let escrow_info = &escrow_state.escrow_info;
+    [
+        (escrow_info.immutables.token.clone(), escrow_state.balance),
+        (AssetKind::Native(escrow_info.safety_deposit_denom.clone()), escrow_info.immutables.safety_deposit),
+    ]
+    .into_iter()
+    .filter(|(_, amount)| !amount.is_zero())
+    .collect()
+}
 
 /// Storage helper functions
-pub fn get_next_escrow_id(storage: &mut dyn cosmwasm_std::Storage) -> StdResult<u64> {
+pub fn get_next_escrow_id(storage: &mut dyn cosmwasm_std::Storage) -> Result<u64, ContractError> {
     let current_id = ESCROW_COUNTER.load(storage).unwrap_or(0);
-    let next_id = current_id + 1;
+    let next_id = current_id.checked_add(1).ok_or_else(|| ContractError::OperationFailed { 
+        reason: "escrow id space exhausted".to_string() 
+    })?;
     ESCROW_COUNTER.save(storage, &next_id)?;
     Ok(next_id)
 }
@@ -1,10 +1,26 @@
-use cosmwasm_std::{Addr, Coin, Uint128};
-use cw_multi_test::{App, Contract, ContractWrapper, Executor};
-use escrow_contract::msg::{InstantiateMsg, QueryMsg, ExecuteMsg};
-use escrow_contract::state::{TimelockStage, PackedTimelocks, EscrowType};
+use cosmwasm_std::{to_json_binary, Addr, Binary, Coin, CosmosMsg, Uint128, WasmMsg};
+use k256::ecdsa::signature::hazmat::PrehashSigner;
+use k256::ecdsa::{Signature, SigningKey};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw721::OwnerOfResponse;
+use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+use escrow_contract::error::ContractError;
+use escrow_contract::msg::{
+    InstantiateMsg, QueryMsg, ExecuteMsg, EscrowsResponse, ReconciliationResponse, Cw20HookMsg,
+    ActionPreviewResponse, EscrowAction, AvailableActionsResponse, CallerRole,
+    CompletedEscrow, CompletedEscrowsResponse, HashlockResponse, ValidateParamsResponse, EscrowPhase,
+    RescueStatusResponse, ConfigResponse, ActionableEscrowsResponse, ActionableEscrow, TotalValueLockedResponse, ImmutablesHashResponse, CanWithdrawResponse, NearRescueEscrow, NearRescueResponse, OwnershipResponse, UncheckedAccessToken, InvariantViolation, InvariantsResponse,
+    MetricsResponse, PendingCancellation, PendingCancellationsResponse,
+};
+use escrow_contract::state::{
+    TimelockStage, PackedTimelocks, EscrowType, EscrowStatus, EscrowState, Immutables, AssetKind, AssetTotal, AccessToken, MIN_RESCUE_DELAY, MIN_SAFETY_DEPOSIT_BPS, MIN_CANCELLATION_OFFSET, MAX_LIFETIME_SECONDS, ESCROW_COUNTER, ESCROWS, MAKER_ESCROWS, WITHDRAW_FEES_PAID, create_escrow, lock_value, release_value,
+    get_next_escrow_id, cancel_authorization_hash, public_withdraw_authorization_hash, public_authority_proof_hash, order_commitment_hash, mutual_cancel_hash,
+};
 use sha2::{Sha256, Digest};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+type Cw721BaseExecuteMsg = cw721_base::ExecuteMsg<cw721_base::Extension, cosmwasm_std::Empty>;
+
 fn escrow_contract() -> Box<dyn Contract<cosmwasm_std::Empty>> {
     let contract = ContractWrapper::new(
         escrow_contract::execute,
@@ -14,9 +30,99 @@ fn escrow_contract() -> Box<dyn Contract<cosmwasm_std::Empty>> {
     Box::new(contract)
 }
 
+fn cw20_contract() -> Box<dyn Contract<cosmwasm_std::Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+fn cw721_contract() -> Box<dyn Contract<cosmwasm_std::Empty>> {
+    let contract = ContractWrapper::new(
+        cw721_base::entry::execute,
+        cw721_base::entry::instantiate,
+        cw721_base::entry::query,
+    );
+    Box::new(contract)
+}
+
+fn instantiate_cw721(app: &mut App, owner: &str, token_id: &str) -> Addr {
+    let cw721_id = app.store_code(cw721_contract());
+    let msg = cw721_base::InstantiateMsg {
+        name: "Test NFT".to_string(),
+        symbol: "NFT".to_string(),
+        minter: "owner".to_string(),
+    };
+    let nft = app
+        .instantiate_contract(cw721_id, Addr::unchecked("owner"), &msg, &[], "CW721", None)
+        .unwrap();
+    let mint: Cw721BaseExecuteMsg = cw721_base::ExecuteMsg::Mint {
+        token_id: token_id.to_string(),
+        owner: owner.to_string(),
+        token_uri: None,
+        extension: None,
+    };
+    app.execute_contract(Addr::unchecked("owner"), nft.clone(), &mint, &[])
+        .unwrap();
+    nft
+}
+
+fn query_nft_owner(app: &App, nft: &Addr, token_id: &str) -> OwnerOfResponse {
+    app.wrap()
+        .query_wasm_smart(
+            nft,
+            &cw721_base::QueryMsg::<cosmwasm_std::Empty>::OwnerOf { token_id: token_id.to_string(), include_expired: None },
+        )
+        .unwrap()
+}
+
+fn instantiate_cw20(app: &mut App, initial_balances: Vec<Cw20Coin>) -> Addr {
+    let cw20_id = app.store_code(cw20_contract());
+    let msg = cw20_base::msg::InstantiateMsg {
+        name: "Test Token".to_string(),
+        symbol: "TEST".to_string(),
+        decimals: 6,
+        initial_balances,
+        mint: None,
+        marketing: None,
+    };
+    app.instantiate_contract(cw20_id, Addr::unchecked("owner"), &msg, &[], "CW20", None)
+        .unwrap()
+}
+
+fn query_cw20_balance(app: &App, token: &Addr, address: &str) -> Uint128 {
+    let response: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(token, &Cw20QueryMsg::Balance { address: address.to_string() })
+        .unwrap();
+    response.balance
+}
+
+/// Deliver an escrow's CW20 principal through `Send` and its receive hook
+fn fund_cw20_escrow(app: &mut App, token: &Addr, contract_addr: &Addr, sender: &str, amount: u128) {
+    app.execute_contract(
+        Addr::unchecked(sender),
+        token.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: contract_addr.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(&Cw20HookMsg::DeployEscrow { escrow_id: 1 }).unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+}
+
+const TEST_ORDER_HASH: &str = "66471393ddc0da762c2d4c76cce511e39c44f5fd7ae88274ca67949bc6bf4fb1";
+const TEST_HASHLOCK: &str = "e763179f9f878ff5dda7028fa3b974f5f47cfb11432244cfd50398e94bb205b1";
+
+const IBC_DENOM: &str = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+
 fn mock_app() -> App {
     App::new(|router, _api, storage| {
-        router.bank.init_balance(storage, &Addr::unchecked("owner"), vec![Coin::new(10000, "uatom")]).unwrap();
+        router.bank.init_balance(storage, &Addr::unchecked("owner"), vec![Coin::new(10000, "uatom"), Coin::new(10000, "uosmo"), Coin::new(10000, IBC_DENOM)]).unwrap();
         router.bank.init_balance(storage, &Addr::unchecked("taker"), vec![Coin::new(2000, "uatom")]).unwrap();
         router.bank.init_balance(storage, &Addr::unchecked("maker"), vec![Coin::new(2000, "uatom")]).unwrap();
     })
@@ -35,12 +141,93 @@ fn create_test_timelocks() -> PackedTimelocks {
     )
 }
 
+fn test_instantiate_msg(escrow_type: EscrowType) -> InstantiateMsg {
+    InstantiateMsg {
+        order_hash: TEST_ORDER_HASH.to_string(),
+        hashlock: TEST_HASHLOCK.to_string(),
+        maker: "maker".to_string(),
+        taker: "taker".to_string(),
+        token: "".to_string(),
+        amount: Uint128::new(1000),
+        safety_deposit: Uint128::new(100),
+        timelocks: create_test_timelocks(),
+        dst_chain_id: "cosmoshub-4".to_string(),
+        dst_token: "dst_token".to_string(),
+        dst_amount: Uint128::new(1000),
+        escrow_type,
+        access_token: UncheckedAccessToken::Cw20("access_token".to_string()),
+        rescue_delay: 86400,
+        creation_fee: Uint128::zero(),
+        rescue_delay_override: None,
+        public_caller_reward: Uint128::new(100),
+        admin_rescue_delay: 604800,
+        public_authority_pubkey: None,
+        withdraw_fee: Uint128::zero(),
+        treasury: None,
+        order_sig: None,
+        default_access_token_threshold: Uint128::zero(),
+        access_token_threshold: None,
+        memo: None,
+        min_dst_amount: None,
+        deposit_contributors: None,
+        check_solvency: false,
+        event_prefix: None,
+        max_timelock_extension_hours: None,
+        timelock_skew_seconds: None,
+        native_denom: None,
+        safety_deposit_denom: None,
+        ibc_memo: None,
+        maker_pubkey: None,
+        token_id: None,
+        min_secret_bytes: None,
+        taker_pubkey: None,
+        withdrawal_deadline_offset: None,
+    }
+}
+
+fn instantiate_escrow(app: &mut App, msg: &InstantiateMsg, funds: &[Coin]) -> Addr {
+    let contract_id = app.store_code(escrow_contract());
+    app.instantiate_contract(contract_id, Addr::unchecked("owner"), msg, funds, "Escrow", None)
+        .unwrap()
+}
+
+fn query_escrow(app: &App, contract_addr: &Addr) -> escrow_contract::msg::EscrowStateResponse {
+    app.wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::EscrowState {})
+        .unwrap()
+}
+
+fn instantiate_escrow_with_response(app: &mut App, msg: &InstantiateMsg, funds: &[Coin]) -> AppResponse {
+    let contract_id = app.store_code(escrow_contract());
+    app.execute(
+        Addr::unchecked("owner"),
+        CosmosMsg::Wasm(WasmMsg::Instantiate {
+            admin: None,
+            code_id: contract_id,
+            msg: to_json_binary(msg).unwrap(),
+            funds: funds.to_vec(),
+            label: "Escrow".to_string(),
+        }),
+    )
+    .unwrap()
+}
+
+fn wasm_attribute(response: &AppResponse, key: &str) -> Option<String> {
+    response
+        .events
+        .iter()
+        .filter(|event| event.ty == "wasm")
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == key)
+        .map(|attr| attr.value.clone())
+}
+
 fn generate_secret() -> String {
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
         .as_secs();
-    format!("secret_{}", timestamp)
+    format!("secret_{:032}", timestamp)
 }
 
 fn hash_secret(secret: &str) -> String {
@@ -55,8 +242,8 @@ fn test_instantiate() {
     let contract_id = app.store_code(escrow_contract());
 
     let msg = InstantiateMsg {
-        order_hash: "test_order_hash_123".to_string(),
-        hashlock: "test_hashlock_456".to_string(),
+        order_hash: TEST_ORDER_HASH.to_string(),
+        hashlock: TEST_HASHLOCK.to_string(),
         maker: "maker".to_string(),
         taker: "taker".to_string(),
         token: "".to_string(),
@@ -76,6 +263,33 @@ fn test_instantiate() {
         dst_token: "dst_token".to_string(),
         dst_amount: Uint128::new(1000),
         escrow_type: EscrowType::Source,
+        access_token: UncheckedAccessToken::Cw20("access_token".to_string()),
+        rescue_delay: 86400,
+        creation_fee: Uint128::zero(),
+        rescue_delay_override: None,
+        public_caller_reward: Uint128::new(100),
+        admin_rescue_delay: 604800,
+        public_authority_pubkey: None,
+        withdraw_fee: Uint128::zero(),
+        treasury: None,
+        order_sig: None,
+        default_access_token_threshold: Uint128::zero(),
+        access_token_threshold: None,
+        memo: None,
+        min_dst_amount: None,
+        deposit_contributors: None,
+        check_solvency: false,
+        event_prefix: None,
+        max_timelock_extension_hours: None,
+        timelock_skew_seconds: None,
+        native_denom: None,
+        safety_deposit_denom: None,
+        ibc_memo: None,
+        maker_pubkey: None,
+        token_id: None,
+        min_secret_bytes: None,
+        taker_pubkey: None,
+        withdrawal_deadline_offset: None,
     };
 
     let contract_addr = app
@@ -83,16 +297,16 @@ fn test_instantiate() {
         .unwrap();
 
     // Query escrow to verify instantiation
-    let config_response: escrow_contract::msg::ConfigResponse = app
+    let escrow_state: escrow_contract::msg::EscrowStateResponse = app
         .wrap()
-        .query_wasm_smart(contract_addr, &QueryMsg::Config {})
+        .query_wasm_smart(contract_addr, &QueryMsg::EscrowState {})
         .unwrap();
 
-    assert_eq!(config_response.escrow_id, 1);
-    assert_eq!(config_response.escrow_type, EscrowType::Source);
-    assert!(config_response.is_active);
-    assert_eq!(config_response.balance, Uint128::new(1000));
-    assert_eq!(config_response.native_balance, Uint128::new(100));
+    assert_eq!(escrow_state.escrow_id, 1);
+    assert_eq!(escrow_state.escrow_type, EscrowType::Source);
+    assert_eq!(escrow_state.status, EscrowStatus::Active);
+    assert_eq!(escrow_state.balance, Uint128::new(1000));
+    assert_eq!(escrow_state.native_balance, Uint128::new(100));
 }
 
 #[test]
@@ -126,7 +340,7 @@ fn test_sophisticated_timelock_system() {
 
     // Test stage time calculations (convert hours to seconds)
     let src_withdrawal_time = timelocks.get_stage_time(TimelockStage::SrcWithdrawal);
-    assert_eq!(src_withdrawal_time, deployed_at as u64 + (1 * 3600)); // 1 hour in seconds
+    assert_eq!(src_withdrawal_time, deployed_at as u64 + 3600); // 1 hour in seconds
 
     let src_public_withdrawal_time = timelocks.get_stage_time(TimelockStage::SrcPublicWithdrawal);
     assert_eq!(src_public_withdrawal_time, deployed_at as u64 + (2 * 3600)); // 2 hours in seconds
@@ -171,8 +385,8 @@ fn test_access_control() {
     let contract_id = app.store_code(escrow_contract());
 
     let msg = InstantiateMsg {
-        order_hash: "test_order_hash_123".to_string(),
-        hashlock: "test_hashlock_456".to_string(),
+        order_hash: TEST_ORDER_HASH.to_string(),
+        hashlock: TEST_HASHLOCK.to_string(),
         maker: "maker".to_string(),
         taker: "taker".to_string(),
         token: "".to_string(),
@@ -192,6 +406,33 @@ fn test_access_control() {
         dst_token: "dst_token".to_string(),
         dst_amount: Uint128::new(1000),
         escrow_type: EscrowType::Source,
+        access_token: UncheckedAccessToken::Cw20("access_token".to_string()),
+        rescue_delay: 86400,
+        creation_fee: Uint128::zero(),
+        rescue_delay_override: None,
+        public_caller_reward: Uint128::new(100),
+        admin_rescue_delay: 604800,
+        public_authority_pubkey: None,
+        withdraw_fee: Uint128::zero(),
+        treasury: None,
+        order_sig: None,
+        default_access_token_threshold: Uint128::zero(),
+        access_token_threshold: None,
+        memo: None,
+        min_dst_amount: None,
+        deposit_contributors: None,
+        check_solvency: false,
+        event_prefix: None,
+        max_timelock_extension_hours: None,
+        timelock_skew_seconds: None,
+        native_denom: None,
+        safety_deposit_denom: None,
+        ibc_memo: None,
+        maker_pubkey: None,
+        token_id: None,
+        min_secret_bytes: None,
+        taker_pubkey: None,
+        withdrawal_deadline_offset: None,
     };
 
     let contract_addr = app
@@ -295,8 +536,8 @@ fn test_direct_escrow_deployment() {
     let contract_id = app.store_code(escrow_contract());
 
     let msg = InstantiateMsg {
-        order_hash: "order_hash_123".to_string(),
-        hashlock: "hashlock_456".to_string(),
+        order_hash: "dfec608cfeba64034161a6986c1baa84b72ad7fa6f740894400b1dfb426985c3".to_string(),
+        hashlock: "a03e62b3ca1ca734df290e4fd9b56603216f75335bd531190589bf5dbedee8f3".to_string(),
         maker: "maker".to_string(),
         taker: "taker".to_string(),
         token: "".to_string(), // Native token
@@ -316,6 +557,33 @@ fn test_direct_escrow_deployment() {
         dst_token: "dst_token".to_string(),
         dst_amount: Uint128::new(1000),
         escrow_type: EscrowType::Source,
+        access_token: UncheckedAccessToken::Cw20("access_token".to_string()),
+        rescue_delay: 86400,
+        creation_fee: Uint128::zero(),
+        rescue_delay_override: None,
+        public_caller_reward: Uint128::new(100),
+        admin_rescue_delay: 604800,
+        public_authority_pubkey: None,
+        withdraw_fee: Uint128::zero(),
+        treasury: None,
+        order_sig: None,
+        default_access_token_threshold: Uint128::zero(),
+        access_token_threshold: None,
+        memo: None,
+        min_dst_amount: None,
+        deposit_contributors: None,
+        check_solvency: false,
+        event_prefix: None,
+        max_timelock_extension_hours: None,
+        timelock_skew_seconds: None,
+        native_denom: None,
+        safety_deposit_denom: None,
+        ibc_memo: None,
+        maker_pubkey: None,
+        token_id: None,
+        min_secret_bytes: None,
+        taker_pubkey: None,
+        withdrawal_deadline_offset: None,
     };
 
     // Execute with funds
@@ -332,16 +600,16 @@ fn test_direct_escrow_deployment() {
     assert!(result.is_ok());
 
     // Query escrows to verify deployment
-    let config_response: escrow_contract::msg::ConfigResponse = app
+    let escrow_state: escrow_contract::msg::EscrowStateResponse = app
         .wrap()
-        .query_wasm_smart(result.unwrap(), &QueryMsg::Config {})
+        .query_wasm_smart(result.unwrap(), &QueryMsg::EscrowState {})
         .unwrap();
 
-    assert_eq!(config_response.escrow_id, 1);
-    assert_eq!(config_response.escrow_type, EscrowType::Source);
-    assert!(config_response.is_active);
-    assert_eq!(config_response.balance, Uint128::new(1000));
-    assert_eq!(config_response.native_balance, Uint128::new(100));
+    assert_eq!(escrow_state.escrow_id, 1);
+    assert_eq!(escrow_state.escrow_type, EscrowType::Source);
+    assert_eq!(escrow_state.status, EscrowStatus::Active);
+    assert_eq!(escrow_state.balance, Uint128::new(1000));
+    assert_eq!(escrow_state.native_balance, Uint128::new(100));
 } 
 
 #[test]
@@ -350,8 +618,8 @@ fn test_destination_escrow_instantiation() {
     let contract_id = app.store_code(escrow_contract());
 
     let msg = InstantiateMsg {
-        order_hash: "test_order_hash_456".to_string(),
-        hashlock: "test_hashlock_789".to_string(),
+        order_hash: "6895a06fdf04e01267fef6a2e10e9f68b8a70c659ea2c828bf91f614c222c392".to_string(),
+        hashlock: "b912e12b836f42178739f62509e4987207f9ae0c4726f8a6e4df3fe10f417fe1".to_string(),
         maker: "maker".to_string(),
         taker: "taker".to_string(),
         token: "".to_string(),
@@ -362,6 +630,33 @@ fn test_destination_escrow_instantiation() {
         dst_token: "dst_token".to_string(),
         dst_amount: Uint128::new(500),
         escrow_type: EscrowType::Destination,
+        access_token: UncheckedAccessToken::Cw20("access_token".to_string()),
+        rescue_delay: 86400,
+        creation_fee: Uint128::zero(),
+        rescue_delay_override: None,
+        public_caller_reward: Uint128::new(100),
+        admin_rescue_delay: 604800,
+        public_authority_pubkey: None,
+        withdraw_fee: Uint128::zero(),
+        treasury: None,
+        order_sig: None,
+        default_access_token_threshold: Uint128::zero(),
+        access_token_threshold: None,
+        memo: None,
+        min_dst_amount: None,
+        deposit_contributors: None,
+        check_solvency: false,
+        event_prefix: None,
+        max_timelock_extension_hours: None,
+        timelock_skew_seconds: None,
+        native_denom: None,
+        safety_deposit_denom: None,
+        ibc_memo: None,
+        maker_pubkey: None,
+        token_id: None,
+        min_secret_bytes: None,
+        taker_pubkey: None,
+        withdrawal_deadline_offset: None,
     };
 
     let contract_addr = app
@@ -369,16 +664,16 @@ fn test_destination_escrow_instantiation() {
         .unwrap();
 
     // Query escrow to verify instantiation
-    let config_response: escrow_contract::msg::ConfigResponse = app
+    let escrow_state: escrow_contract::msg::EscrowStateResponse = app
         .wrap()
-        .query_wasm_smart(contract_addr, &QueryMsg::Config {})
+        .query_wasm_smart(contract_addr, &QueryMsg::EscrowState {})
         .unwrap();
 
-    assert_eq!(config_response.escrow_id, 1);
-    assert_eq!(config_response.escrow_type, EscrowType::Destination);
-    assert!(config_response.is_active);
-    assert_eq!(config_response.balance, Uint128::new(500));
-    assert_eq!(config_response.native_balance, Uint128::new(50));
+    assert_eq!(escrow_state.escrow_id, 1);
+    assert_eq!(escrow_state.escrow_type, EscrowType::Destination);
+    assert_eq!(escrow_state.status, EscrowStatus::Active);
+    assert_eq!(escrow_state.balance, Uint128::new(500));
+    assert_eq!(escrow_state.native_balance, Uint128::new(50));
 }
 
 #[test]
@@ -387,8 +682,8 @@ fn test_insufficient_funds_instantiation() {
     let contract_id = app.store_code(escrow_contract());
 
     let msg = InstantiateMsg {
-        order_hash: "test_order_hash_123".to_string(),
-        hashlock: "test_hashlock_456".to_string(),
+        order_hash: TEST_ORDER_HASH.to_string(),
+        hashlock: TEST_HASHLOCK.to_string(),
         maker: "maker".to_string(),
         taker: "taker".to_string(),
         token: "".to_string(),
@@ -399,6 +694,33 @@ fn test_insufficient_funds_instantiation() {
         dst_token: "dst_token".to_string(),
         dst_amount: Uint128::new(1000),
         escrow_type: EscrowType::Source,
+        access_token: UncheckedAccessToken::Cw20("access_token".to_string()),
+        rescue_delay: 86400,
+        creation_fee: Uint128::zero(),
+        rescue_delay_override: None,
+        public_caller_reward: Uint128::new(100),
+        admin_rescue_delay: 604800,
+        public_authority_pubkey: None,
+        withdraw_fee: Uint128::zero(),
+        treasury: None,
+        order_sig: None,
+        default_access_token_threshold: Uint128::zero(),
+        access_token_threshold: None,
+        memo: None,
+        min_dst_amount: None,
+        deposit_contributors: None,
+        check_solvency: false,
+        event_prefix: None,
+        max_timelock_extension_hours: None,
+        timelock_skew_seconds: None,
+        native_denom: None,
+        safety_deposit_denom: None,
+        ibc_memo: None,
+        maker_pubkey: None,
+        token_id: None,
+        min_secret_bytes: None,
+        taker_pubkey: None,
+        withdrawal_deadline_offset: None,
     };
 
     // Try to instantiate with insufficient funds
@@ -423,7 +745,7 @@ fn test_withdrawal_with_correct_secret() {
     let hashlock = hash_secret(&secret);
 
     let msg = InstantiateMsg {
-        order_hash: "test_order_hash_123".to_string(),
+        order_hash: TEST_ORDER_HASH.to_string(),
         hashlock: hashlock.clone(),
         maker: "maker".to_string(),
         taker: "taker".to_string(),
@@ -435,6 +757,33 @@ fn test_withdrawal_with_correct_secret() {
         dst_token: "dst_token".to_string(),
         dst_amount: Uint128::new(1000),
         escrow_type: EscrowType::Source,
+        access_token: UncheckedAccessToken::Cw20("access_token".to_string()),
+        rescue_delay: 86400,
+        creation_fee: Uint128::zero(),
+        rescue_delay_override: None,
+        public_caller_reward: Uint128::new(100),
+        admin_rescue_delay: 604800,
+        public_authority_pubkey: None,
+        withdraw_fee: Uint128::zero(),
+        treasury: None,
+        order_sig: None,
+        default_access_token_threshold: Uint128::zero(),
+        access_token_threshold: None,
+        memo: None,
+        min_dst_amount: None,
+        deposit_contributors: None,
+        check_solvency: false,
+        event_prefix: None,
+        max_timelock_extension_hours: None,
+        timelock_skew_seconds: None,
+        native_denom: None,
+        safety_deposit_denom: None,
+        ibc_memo: None,
+        maker_pubkey: None,
+        token_id: None,
+        min_secret_bytes: None,
+        taker_pubkey: None,
+        withdrawal_deadline_offset: None,
     };
 
     let contract_addr = app
@@ -444,7 +793,8 @@ fn test_withdrawal_with_correct_secret() {
     // Try to withdraw with correct secret (will fail due to timelock, but not due to secret)
     let withdraw_msg = ExecuteMsg::WithdrawSrc {
         escrow_id: 1,
-        secret: secret,
+        secret,
+        recipient: None,
     };
 
     let result = app.execute_contract(
@@ -456,4 +806,4866 @@ fn test_withdrawal_with_correct_secret() {
 
     // Should fail due to timelock, not secret validation
     assert!(result.is_err());
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_creation_fee_underpayment_rejected() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    let msg = InstantiateMsg {
+        creation_fee: Uint128::new(10),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+
+    // amount + safety_deposit without the creation fee
+    let result = app.instantiate_contract(
+        contract_id,
+        Addr::unchecked("owner"),
+        &msg,
+        &[Coin::new(1100, "uatom")],
+        "Escrow",
+        None,
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_creation_fee_accrual_and_withdrawal() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    let msg = InstantiateMsg {
+        creation_fee: Uint128::new(10),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+
+    let contract_addr = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &msg, &[Coin::new(1110, "uatom")], "Escrow", None)
+        .unwrap();
+
+    // Escrow balances exclude the fee
+    let escrow_state: escrow_contract::msg::EscrowStateResponse = app
+        .wrap()
+        .query_wasm_smart(contract_addr.clone(), &QueryMsg::EscrowState {})
+        .unwrap();
+    assert_eq!(escrow_state.balance, Uint128::new(1000));
+    assert_eq!(escrow_state.native_balance, Uint128::new(100));
+
+    // Only owner can withdraw fees
+    let withdraw_fees_msg = ExecuteMsg::WithdrawFees {
+        recipient: "treasury".to_string(),
+    };
+    let result = app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &withdraw_fees_msg, &[]);
+    assert!(result.is_err());
+
+    app.execute_contract(Addr::unchecked("owner"), contract_addr.clone(), &withdraw_fees_msg, &[])
+        .unwrap();
+
+    let treasury_balance = app.wrap().query_balance("treasury", "uatom").unwrap();
+    assert_eq!(treasury_balance.amount, Uint128::new(10));
+
+    // Fees are reset after withdrawal
+    let result = app.execute_contract(Addr::unchecked("owner"), contract_addr, &withdraw_fees_msg, &[]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_maker_cancel_src_after_public_cancellation() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    let contract_addr = app
+        .instantiate_contract(
+            contract_id,
+            Addr::unchecked("owner"),
+            &test_instantiate_msg(EscrowType::Source),
+            &[Coin::new(1100, "uatom")],
+            "Escrow",
+            None,
+        )
+        .unwrap();
+
+    let deployed_at = app.block_info().time;
+    let cancel_msg = ExecuteMsg::CancelSrc { escrow_id: 1 };
+
+    // Private cancellation window (3h): maker is not allowed yet
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    let err = app
+        .execute_contract(Addr::unchecked("maker"), contract_addr.clone(), &cancel_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OnlyTaker {}
+    );
+
+    // Unrelated callers are never allowed
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+    let result = app.execute_contract(Addr::unchecked("owner"), contract_addr.clone(), &cancel_msg, &[]);
+    assert!(result.is_err());
+
+    // Public cancellation window (4h): maker can reclaim its funds,
+    // while the deposit still goes back to the creator who posted it
+    app.execute_contract(Addr::unchecked("maker"), contract_addr, &cancel_msg, &[])
+        .unwrap();
+
+    let maker_balance = app.wrap().query_balance("maker", "uatom").unwrap();
+    assert_eq!(maker_balance.amount, Uint128::new(2000 + 1000));
+    let taker_balance = app.wrap().query_balance("taker", "uatom").unwrap();
+    assert_eq!(taker_balance.amount, Uint128::new(2000));
+    let owner_balance = app.wrap().query_balance("owner", "uatom").unwrap();
+    assert_eq!(owner_balance.amount, Uint128::new(10000 - 1100 + 100));
+}
+
+#[test]
+fn test_creator_recorded_and_refunded_deposit() {
+    let mut app = mock_app();
+    app.init_modules(|router, _api, storage| {
+        router.bank.init_balance(storage, &Addr::unchecked("relayer"), vec![Coin::new(1100, "uatom")])
+    })
+    .unwrap();
+
+    // A relayer funds the escrow on behalf of maker and taker
+    let contract_id = app.store_code(escrow_contract());
+    let contract_addr = app
+        .instantiate_contract(
+            contract_id,
+            Addr::unchecked("relayer"),
+            &test_instantiate_msg(EscrowType::Source),
+            &[Coin::new(1100, "uatom")],
+            "Escrow",
+            None,
+        )
+        .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).creator, "relayer");
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    app.execute_contract(Addr::unchecked("taker"), contract_addr, &ExecuteMsg::CancelSrc { escrow_id: 1 }, &[])
+        .unwrap();
+
+    // The principal goes back to the maker, the deposit to the relayer that posted it
+    assert_eq!(app.wrap().query_balance("maker", "uatom").unwrap().amount, Uint128::new(2000 + 1000));
+    assert_eq!(app.wrap().query_balance("relayer", "uatom").unwrap().amount, Uint128::new(100));
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(2000));
+}
+
+#[test]
+fn test_taker_cancel_src_in_private_window() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    let contract_addr = app
+        .instantiate_contract(
+            contract_id,
+            Addr::unchecked("owner"),
+            &test_instantiate_msg(EscrowType::Source),
+            &[Coin::new(1100, "uatom")],
+            "Escrow",
+            None,
+        )
+        .unwrap();
+
+    let deployed_at = app.block_info().time;
+    let cancel_msg = ExecuteMsg::CancelSrc { escrow_id: 1 };
+
+    // Before cancellation stage
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600));
+    let result = app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &cancel_msg, &[]);
+    assert!(result.is_err());
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    app.execute_contract(Addr::unchecked("taker"), contract_addr, &cancel_msg, &[])
+        .unwrap();
+
+    let maker_balance = app.wrap().query_balance("maker", "uatom").unwrap();
+    assert_eq!(maker_balance.amount, Uint128::new(2000 + 1000));
+    let taker_balance = app.wrap().query_balance("taker", "uatom").unwrap();
+    assert_eq!(taker_balance.amount, Uint128::new(2000));
+    let owner_balance = app.wrap().query_balance("owner", "uatom").unwrap();
+    assert_eq!(owner_balance.amount, Uint128::new(10000 - 1100 + 100));
+}
+
+#[test]
+fn test_final_status_withdrawn() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+
+    let withdraw_msg = ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None };
+    app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &withdraw_msg, &[])
+        .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Withdrawn);
+
+    // Acting on a completed escrow reports its final status
+    let err = app
+        .execute_contract(Addr::unchecked("taker"), contract_addr, &withdraw_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::EscrowAlreadyCompleted { escrow_id: 1, status: EscrowStatus::Withdrawn }
+    );
+}
+
+#[test]
+fn test_final_status_cancelled() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Destination),
+        &[Coin::new(1100, "uatom")],
+    );
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+
+    let cancel_msg = ExecuteMsg::CancelDst { escrow_id: 1 };
+    app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &cancel_msg, &[])
+        .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Cancelled);
+
+    let err = app
+        .execute_contract(Addr::unchecked("taker"), contract_addr, &cancel_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::EscrowAlreadyCompleted { escrow_id: 1, status: EscrowStatus::Cancelled }
+    );
+}
+
+#[test]
+fn test_final_status_rescued() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400));
+
+    let rescue_msg = ExecuteMsg::Rescue { escrow_id: 1 };
+    app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &rescue_msg, &[])
+        .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Rescued);
+
+    let err = app
+        .execute_contract(Addr::unchecked("taker"), contract_addr, &rescue_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::EscrowAlreadyCompleted { escrow_id: 1, status: EscrowStatus::Rescued }
+    );
+}
+
+#[test]
+fn test_funding_rejects_stray_denom() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    let result = app.instantiate_contract(
+        contract_id,
+        Addr::unchecked("owner"),
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom"), Coin::new(5, "uosmo")],
+        "Escrow",
+        None,
+    );
+
+    let err = result.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(err, ContractError::InvalidAmount { amount: "5uosmo".to_string() });
+}
+
+#[test]
+fn test_funding_rejects_multiple_entries() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    let result = app.instantiate_contract(
+        contract_id,
+        Addr::unchecked("owner"),
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(600, "uatom"), Coin::new(500, "uatom")],
+        "Escrow",
+        None,
+    );
+
+    let err = result.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(err, ContractError::InvalidAmount { amount: "600uatom,500uatom".to_string() });
+}
+
+#[test]
+fn test_rescue_delay_override() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg {
+        rescue_delay_override: Some(2 * 86400),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    let rescue_msg = ExecuteMsg::Rescue { escrow_id: 1 };
+
+    // Global delay has passed but the per-escrow override has not
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400));
+    let result = app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &rescue_msg, &[]);
+    assert!(result.is_err());
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 86400));
+    app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &rescue_msg, &[])
+        .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Rescued);
+}
+
+#[test]
+fn test_rescue_delay_fallback_to_config() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Destination),
+        &[Coin::new(1100, "uatom")],
+    );
+
+    let deployed_at = app.block_info().time;
+    let rescue_msg = ExecuteMsg::Rescue { escrow_id: 1 };
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400 - 1));
+    let result = app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &rescue_msg, &[]);
+    assert!(result.is_err());
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400));
+    app.execute_contract(Addr::unchecked("taker"), contract_addr, &rescue_msg, &[])
+        .unwrap();
+}
+
+#[test]
+fn test_rescue_delay_override_below_minimum() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    let msg = InstantiateMsg {
+        rescue_delay_override: Some(60),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let result = app.instantiate_contract(
+        contract_id,
+        Addr::unchecked("owner"),
+        &msg,
+        &[Coin::new(1100, "uatom")],
+        "Escrow",
+        None,
+    );
+
+    let err = result.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        err,
+        ContractError::InvalidTime { reason: format!("Rescue delay 60 is below minimum {MIN_RESCUE_DELAY}") }
+    );
+
+    // The floor is the contract's own, so a short default delay can't slip under it either
+    let msg = InstantiateMsg {
+        rescue_delay: 60,
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &msg, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTime { reason: format!("Rescue delay 60 is below minimum {MIN_RESCUE_DELAY}") }
+    );
+}
+
+#[test]
+fn test_reconciliation_totals() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        creation_fee: Uint128::new(10),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1110, "uatom")]);
+
+    let reconciliation: ReconciliationResponse = app
+        .wrap()
+        .query_wasm_smart(contract_addr.clone(), &QueryMsg::Reconciliation {})
+        .unwrap();
+    assert_eq!(reconciliation.active_escrows, 1);
+    assert_eq!(reconciliation.total_balance, Uint128::new(1000));
+    assert_eq!(reconciliation.total_native_balance, Uint128::new(100));
+
+    // Tracked totals plus collected fees match the actual contract holdings
+    let contract_balance = app.wrap().query_balance(contract_addr.clone(), "uatom").unwrap();
+    assert_eq!(
+        contract_balance.amount,
+        reconciliation.total_balance + reconciliation.total_native_balance + Uint128::new(10)
+    );
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+
+    let reconciliation: ReconciliationResponse = app
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::Reconciliation {})
+        .unwrap();
+    assert_eq!(reconciliation.active_escrows, 0);
+    assert_eq!(reconciliation.total_balance, Uint128::zero());
+    assert_eq!(reconciliation.total_native_balance, Uint128::zero());
+}
+
+#[test]
+fn test_maker_public_withdraw_dst() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Destination)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    let public_withdraw_msg = ExecuteMsg::PublicWithdrawDst { escrow_id: 1, proof_sig: None, secret: Some(secret.clone()) };
+
+    // Private withdrawal window: public path not open yet
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    let result = app.execute_contract(Addr::unchecked("maker"), contract_addr.clone(), &public_withdraw_msg, &[]);
+    assert!(result.is_err());
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600));
+
+    // Neither access token holder nor maker, even with the secret
+    let err = app
+        .execute_contract(Addr::unchecked("owner"), contract_addr.clone(), &public_withdraw_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OnlyAccessTokenHolder {}
+    );
+
+    // The maker can't self-trigger without the secret, or with a wrong one
+    let err = app
+        .execute_contract(
+            Addr::unchecked("maker"),
+            contract_addr.clone(),
+            &ExecuteMsg::PublicWithdrawDst { escrow_id: 1, proof_sig: None, secret: None },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OnlyAccessTokenHolder {}
+    );
+    let err = app
+        .execute_contract(
+            Addr::unchecked("maker"),
+            contract_addr.clone(),
+            &ExecuteMsg::PublicWithdrawDst { escrow_id: 1, proof_sig: None, secret: Some("x".repeat(32)) },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSecret {});
+
+    app.execute_contract(Addr::unchecked("maker"), contract_addr.clone(), &public_withdraw_msg, &[])
+        .unwrap();
+
+    // Maker receives principal and, as caller, the safety deposit
+    let maker_balance = app.wrap().query_balance("maker", "uatom").unwrap();
+    assert_eq!(maker_balance.amount, Uint128::new(2000 + 1000 + 100));
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Withdrawn);
+}
+
+#[test]
+fn test_taker_public_withdraw_src() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600));
+
+    // Without the preimage the taker is just another caller
+    let err = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr.clone(),
+            &ExecuteMsg::PublicWithdrawSrc { escrow_id: 1, proof_sig: None, secret: None },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OnlyAccessTokenHolder {}
+    );
+
+    // A short secret fails the same length floor as WithdrawSrc
+    let err = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr.clone(),
+            &ExecuteMsg::PublicWithdrawSrc { escrow_id: 1, proof_sig: None, secret: Some("short".to_string()) },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::SecretTooShort { .. }));
+
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::PublicWithdrawSrc { escrow_id: 1, proof_sig: None, secret: Some(secret) },
+        &[],
+    )
+    .unwrap();
+
+    let taker_balance = app.wrap().query_balance("taker", "uatom").unwrap();
+    assert_eq!(taker_balance.amount, Uint128::new(2000 + 1000 + 100));
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Withdrawn);
+}
+
+#[test]
+fn test_funding_overflow_is_graceful() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    let msg = InstantiateMsg {
+        amount: Uint128::MAX,
+        safety_deposit: Uint128::new(1),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let result = app.instantiate_contract(
+        contract_id,
+        Addr::unchecked("owner"),
+        &msg,
+        &[Coin::new(1100, "uatom")],
+        "Escrow",
+        None,
+    );
+
+    let err = result.unwrap_err().downcast::<ContractError>().unwrap();
+    assert!(matches!(err, ContractError::InvalidAmount { .. }));
+}
+
+#[test]
+fn test_order_hash_index_and_events() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+
+    let contract_addr = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &msg, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap();
+
+    let escrows: EscrowsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr.clone(),
+            &QueryMsg::EscrowsByOrderHash { order_hash: msg.order_hash.clone() },
+        )
+        .unwrap();
+    assert_eq!(escrows.escrows.len(), 1);
+    assert_eq!(escrows.escrows[0].escrow_id, 1);
+    assert_eq!(escrows.escrows[0].immutables.order_hash, msg.order_hash);
+
+    let escrows: EscrowsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            contract_addr.clone(),
+            &QueryMsg::EscrowsByOrderHash { order_hash: "unknown".to_string() },
+        )
+        .unwrap();
+    assert!(escrows.escrows.is_empty());
+
+    // Lifecycle events carry the order hash
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    let res = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr,
+            &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+            &[],
+        )
+        .unwrap();
+    let wasm_event = res.events.iter().find(|event| event.ty == "wasm").unwrap();
+    assert!(wasm_event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "order_hash" && attr.value == msg.order_hash));
+}
+
+#[test]
+fn test_public_cancel_reward_split() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg {
+        public_caller_reward: Uint128::new(30),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+
+    app.execute_contract(
+        Addr::unchecked("access_token"),
+        contract_addr,
+        &ExecuteMsg::PublicCancelSrc { escrow_id: 1, proof_sig: None },
+        &[],
+    )
+    .unwrap();
+
+    // Caller gets the capped reward, maker the principal and the creator the remainder
+    let caller_balance = app.wrap().query_balance("access_token", "uatom").unwrap();
+    assert_eq!(caller_balance.amount, Uint128::new(30));
+    let maker_balance = app.wrap().query_balance("maker", "uatom").unwrap();
+    assert_eq!(maker_balance.amount, Uint128::new(2000 + 1000));
+    let creator_balance = app.wrap().query_balance("owner", "uatom").unwrap();
+    assert_eq!(creator_balance.amount, Uint128::new(10000 - 1100 + 70));
+}
+
+#[test]
+fn test_public_cancel_reward_capped_by_deposit() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg {
+        public_caller_reward: Uint128::new(500),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+
+    app.execute_contract(
+        Addr::unchecked("access_token"),
+        contract_addr,
+        &ExecuteMsg::PublicCancelSrc { escrow_id: 1, proof_sig: None },
+        &[],
+    )
+    .unwrap();
+
+    let caller_balance = app.wrap().query_balance("access_token", "uatom").unwrap();
+    assert_eq!(caller_balance.amount, Uint128::new(100));
+    let maker_balance = app.wrap().query_balance("maker", "uatom").unwrap();
+    assert_eq!(maker_balance.amount, Uint128::new(2000 + 1000));
+}
+
+fn query_action_preview(app: &App, contract_addr: &Addr, action: EscrowAction) -> ActionPreviewResponse {
+    app.wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::ActionPreview { escrow_id: 1, action })
+        .unwrap()
+}
+
+#[test]
+fn test_action_preview_matches_withdrawal() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    // Not yet permitted before the withdrawal stage
+    let preview = query_action_preview(&app, &contract_addr, EscrowAction::WithdrawSrc);
+    assert!(!preview.permitted);
+
+    // Wrong escrow type is never permitted
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    let preview = query_action_preview(&app, &contract_addr, EscrowAction::WithdrawDst);
+    assert!(!preview.permitted);
+
+    let preview = query_action_preview(&app, &contract_addr, EscrowAction::WithdrawSrc);
+    assert!(preview.permitted);
+    assert_eq!(preview.recipient, Addr::unchecked("taker"));
+    assert_eq!(preview.amount, Uint128::new(1000));
+    assert_eq!(preview.deposit_recipient, None);
+    assert_eq!(preview.deposit_amount, Uint128::new(100));
+
+    let taker_before = app.wrap().query_balance("taker", "uatom").unwrap().amount;
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+    let taker_after = app.wrap().query_balance("taker", "uatom").unwrap().amount;
+    assert_eq!(taker_after - taker_before, preview.amount + preview.deposit_amount);
+
+    // Completed escrows permit nothing
+    let preview = query_action_preview(&app, &contract_addr, EscrowAction::WithdrawSrc);
+    assert!(!preview.permitted);
+}
+
+#[test]
+fn test_action_preview_matches_public_cancellation() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg {
+        public_caller_reward: Uint128::new(40),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+
+    let preview = query_action_preview(&app, &contract_addr, EscrowAction::PublicCancelSrc);
+    assert!(preview.permitted);
+    assert_eq!(preview.recipient, Addr::unchecked("maker"));
+    assert_eq!(preview.deposit_amount, Uint128::new(40));
+    assert_eq!(preview.deposit_refund, Uint128::new(60));
+
+    app.execute_contract(
+        Addr::unchecked("access_token"),
+        contract_addr,
+        &ExecuteMsg::PublicCancelSrc { escrow_id: 1, proof_sig: None },
+        &[],
+    )
+    .unwrap();
+
+    let caller_balance = app.wrap().query_balance("access_token", "uatom").unwrap();
+    assert_eq!(caller_balance.amount, preview.deposit_amount);
+    let maker_balance = app.wrap().query_balance("maker", "uatom").unwrap();
+    assert_eq!(maker_balance.amount, Uint128::new(2000) + preview.amount);
+    let creator_balance = app.wrap().query_balance("owner", "uatom").unwrap();
+    assert_eq!(creator_balance.amount, Uint128::new(10000 - 1100) + preview.deposit_refund);
+}
+
+#[test]
+fn test_degenerate_timelock_window_rejected() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    // Destination progression ends two hours after deployment
+    let msg = InstantiateMsg {
+        timelocks: PackedTimelocks::new(1000, 1, 2, 3, 4, 0, 1, 2),
+        ..test_instantiate_msg(EscrowType::Destination)
+    };
+    let result = app.instantiate_contract(
+        contract_id,
+        Addr::unchecked("owner"),
+        &msg,
+        &[Coin::new(1100, "uatom")],
+        "Escrow",
+        None,
+    );
+
+    let err = result.unwrap_err().downcast::<ContractError>().unwrap();
+    assert_eq!(
+        err,
+        ContractError::InvalidTime {
+            reason: "Timelock progression spans 7200s up to DstCancellation, minimum is 10800s".to_string()
+        }
+    );
+
+    // Early withdrawal stages are fine for a source escrow whose progression is longer
+    let msg = InstantiateMsg {
+        timelocks: PackedTimelocks::new(1000, 0, 1, 3, 4, 1, 2, 3),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let result = app.instantiate_contract(
+        contract_id,
+        Addr::unchecked("owner"),
+        &msg,
+        &[Coin::new(1100, "uatom")],
+        "Escrow",
+        None,
+    );
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_cw20_principal_cancel_routes_both_legs() {
+    let mut app = mock_app();
+    let token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin { address: "owner".to_string(), amount: Uint128::new(5000) }],
+    );
+
+    // Native funds only cover the safety deposit for a CW20 principal
+    let msg = InstantiateMsg {
+        token: token.to_string(),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(100, "uatom")]);
+
+    // Creator delivers the CW20 principal
+    fund_cw20_escrow(&mut app, &token, &contract_addr, "owner", 1000);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::CancelSrc { escrow_id: 1 },
+        &[],
+    )
+    .unwrap();
+
+    // CW20 principal goes back to the maker, native deposit to the creator
+    assert_eq!(query_cw20_balance(&app, &token, "maker"), Uint128::new(1000));
+    assert_eq!(query_cw20_balance(&app, &token, contract_addr.as_str()), Uint128::zero());
+    let maker_balance = app.wrap().query_balance("maker", "uatom").unwrap();
+    assert_eq!(maker_balance.amount, Uint128::new(2000));
+    let taker_balance = app.wrap().query_balance("taker", "uatom").unwrap();
+    assert_eq!(taker_balance.amount, Uint128::new(2000));
+    let owner_balance = app.wrap().query_balance("owner", "uatom").unwrap();
+    assert_eq!(owner_balance.amount, Uint128::new(10000 - 100 + 100));
+    let contract_balance = app.wrap().query_balance(contract_addr, "uatom").unwrap();
+    assert_eq!(contract_balance.amount, Uint128::zero());
+}
+
+#[test]
+fn test_cw20_token_address_is_normalized() {
+    let mut app = mock_app();
+    let token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin { address: "owner".to_string(), amount: Uint128::new(2000) }],
+    );
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        token: token.as_str().to_uppercase(),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let withdrawn = instantiate_escrow(&mut app, &msg, &[Coin::new(100, "uatom")]);
+    let cancelled = instantiate_escrow(&mut app, &msg, &[Coin::new(100, "uatom")]);
+    assert_eq!(query_escrow(&app, &withdrawn).immutables.token, AssetKind::Cw20(token.clone()));
+
+    // The token contract's own address matches the stored form in the hook
+    for contract_addr in [&withdrawn, &cancelled] {
+        app.execute_contract(
+            Addr::unchecked("owner"),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contract_addr.to_string(),
+                amount: Uint128::new(1000),
+                msg: to_json_binary(&Cw20HookMsg::DeployEscrow { escrow_id: 1 }).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        withdrawn,
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_cw20_balance(&app, &token, "taker"), Uint128::new(1000));
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        cancelled,
+        &ExecuteMsg::CancelSrc { escrow_id: 1 },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_cw20_balance(&app, &token, "maker"), Uint128::new(1000));
+}
+
+#[test]
+fn test_cw20_principal_requires_only_native_deposit() {
+    let mut app = mock_app();
+    let token = instantiate_cw20(&mut app, vec![]);
+    let contract_id = app.store_code(escrow_contract());
+
+    // Sending the principal amount natively would leave it stuck
+    let msg = InstantiateMsg {
+        token: token.to_string(),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let result = app.instantiate_contract(
+        contract_id,
+        Addr::unchecked("owner"),
+        &msg,
+        &[Coin::new(1100, "uatom")],
+        "Escrow",
+        None,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_creation_event_includes_destination_complement() {
+    let mut app = mock_app();
+    let msg = test_instantiate_msg(EscrowType::Source);
+    let response = instantiate_escrow_with_response(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    assert_eq!(wasm_attribute(&response, "dst_chain_id"), Some("cosmoshub-4".to_string()));
+    assert_eq!(wasm_attribute(&response, "dst_token"), Some("dst_token".to_string()));
+    assert_eq!(wasm_attribute(&response, "dst_amount"), Some("1000".to_string()));
+    assert_eq!(wasm_attribute(&response, "dst_safety_deposit"), Some("100".to_string()));
+    assert_eq!(wasm_attribute(&response, "hashlock"), Some(msg.hashlock));
+
+    // Destination escrows carry no complement
+    let response = instantiate_escrow_with_response(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Destination),
+        &[Coin::new(1100, "uatom")],
+    );
+    assert_eq!(wasm_attribute(&response, "dst_chain_id"), None);
+}
+
+#[test]
+fn test_withdrawal_rejected_after_cancellation_starts() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr.clone(),
+            &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret: secret.clone(), recipient: None },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TimelockExpired { stage: "SrcPublicWithdrawal".to_string() }
+    );
+
+    let result = app.execute_contract(
+        Addr::unchecked("access_token"),
+        contract_addr.clone(),
+        &ExecuteMsg::PublicWithdrawSrc { escrow_id: 1, proof_sig: None, secret: None },
+        &[],
+    );
+    assert!(result.is_err());
+
+    // Last second of the public withdrawal window still works
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600 - 1));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr,
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_destination_withdrawal_rejected_after_cancellation_starts() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Destination)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr.clone(),
+            &ExecuteMsg::WithdrawDst { escrow_id: 1, secret },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TimelockExpired { stage: "DstWithdrawal".to_string() }
+    );
+
+    let result = app.execute_contract(
+        Addr::unchecked("maker"),
+        contract_addr,
+        &ExecuteMsg::PublicWithdrawDst { escrow_id: 1, proof_sig: None, secret: None },
+        &[],
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_cancel_rejected_after_secret_revealed() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    // Taker reveals in the last second before cancellation opens
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600 - 1));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+
+    // The reveal is recorded as Withdrawn, which no cancellation path accepts
+    let already_withdrawn = ContractError::EscrowAlreadyCompleted { escrow_id: 1, status: EscrowStatus::Withdrawn };
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    let err = app
+        .execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &ExecuteMsg::CancelSrc { escrow_id: 1 }, &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), already_withdrawn);
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+    let err = app
+        .execute_contract(Addr::unchecked("maker"), contract_addr.clone(), &ExecuteMsg::CancelSrc { escrow_id: 1 }, &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), already_withdrawn);
+    let result = app.execute_contract(
+        Addr::unchecked("access_token"),
+        contract_addr.clone(),
+        &ExecuteMsg::PublicCancelSrc { escrow_id: 1, proof_sig: None },
+        &[],
+    );
+    assert!(result.is_err());
+
+    // Maker got nothing back, the taker kept the principal and deposit
+    assert_eq!(app.wrap().query_balance("maker", "uatom").unwrap().amount, Uint128::new(2000));
+    assert_eq!(load_escrow_state(&app, &contract_addr).escrow_info.status, EscrowStatus::Withdrawn);
+}
+
+#[test]
+fn test_destination_cancel_rejected_after_secret_revealed() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Destination)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawDst { escrow_id: 1, secret },
+        &[],
+    )
+    .unwrap();
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    for caller in ["taker", "maker"] {
+        let err = app
+            .execute_contract(Addr::unchecked(caller), contract_addr.clone(), &ExecuteMsg::CancelDst { escrow_id: 1 }, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::EscrowAlreadyCompleted { escrow_id: 1, status: EscrowStatus::Withdrawn }
+        );
+    }
+}
+
+#[test]
+fn test_cancel_rejected_after_reveal_secret() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::RevealSecret { escrow_id: 1, secret },
+        &[],
+    )
+    .unwrap();
+
+    // The escrow stays active, yet neither party can take the refund
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Active);
+    for caller in ["taker", "maker"] {
+        let err = app
+            .execute_contract(Addr::unchecked(caller), contract_addr.clone(), &ExecuteMsg::CancelSrc { escrow_id: 1 }, &[])
+            .unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::SecretAlreadyRevealed { escrow_id: 1 });
+    }
+    assert!(!query_action_preview(&app, &contract_addr, EscrowAction::CancelSrc).permitted);
+    assert_eq!(app.wrap().query_balance(&contract_addr, "uatom").unwrap().amount, Uint128::new(1100));
+}
+
+#[test]
+fn test_stage_window_boundaries() {
+    let timelocks = create_test_timelocks();
+    let deployed_at = 1000u64;
+    let hour = 3600u64;
+
+    let expected = [
+        (TimelockStage::SrcWithdrawal, hour, Some(2 * hour)),
+        (TimelockStage::SrcPublicWithdrawal, 2 * hour, Some(3 * hour)),
+        (TimelockStage::SrcCancellation, 3 * hour, Some(4 * hour)),
+        (TimelockStage::SrcPublicCancellation, 4 * hour, None),
+        (TimelockStage::DstWithdrawal, hour, Some(2 * hour)),
+        (TimelockStage::DstPublicWithdrawal, 2 * hour, Some(3 * hour)),
+        (TimelockStage::DstCancellation, 3 * hour, None),
+    ];
+
+    for (stage, start, end) in expected {
+        let start = deployed_at + start;
+        let end = end.map(|end| deployed_at + end);
+        assert_eq!(timelocks.stage_window(stage), (start, end), "{stage:?}");
+
+        assert!(!timelocks.is_within_window(start - 1, stage), "{stage:?}");
+        assert!(timelocks.is_within_window(start, stage), "{stage:?}");
+        match end {
+            Some(end) => {
+                assert!(timelocks.is_within_window(end - 1, stage), "{stage:?}");
+                assert!(!timelocks.is_within_window(end, stage), "{stage:?}");
+                // "Has started" keeps returning true past the window
+                assert!(timelocks.is_within_stage(end, stage), "{stage:?}");
+            }
+            None => assert!(timelocks.is_within_window(u64::MAX, stage), "{stage:?}"),
+        }
+    }
+}
+
+fn query_available_actions(app: &App, contract_addr: &Addr) -> Vec<(EscrowAction, bool, CallerRole)> {
+    let response: AvailableActionsResponse = app
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::AvailableActions { escrow_id: 1 })
+        .unwrap();
+    response
+        .actions
+        .into_iter()
+        .map(|available| (available.action, available.requires_secret, available.caller_role))
+        .collect()
+}
+
+#[test]
+fn test_available_actions_across_source_stages() {
+    let mut app = mock_app();
+    let msg = test_instantiate_msg(EscrowType::Source);
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+
+    assert_eq!(query_available_actions(&app, &contract_addr), vec![]);
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    assert_eq!(
+        query_available_actions(&app, &contract_addr),
+        vec![(EscrowAction::WithdrawSrc, true, CallerRole::Taker)]
+    );
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600));
+    assert_eq!(
+        query_available_actions(&app, &contract_addr),
+        vec![
+            (EscrowAction::WithdrawSrc, true, CallerRole::Taker),
+            (EscrowAction::PublicWithdrawSrc, false, CallerRole::AccessTokenOrTakerWithSecret),
+        ]
+    );
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    assert_eq!(
+        query_available_actions(&app, &contract_addr),
+        vec![(EscrowAction::CancelSrc, false, CallerRole::Taker)]
+    );
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+    assert_eq!(
+        query_available_actions(&app, &contract_addr),
+        vec![
+            (EscrowAction::CancelSrc, false, CallerRole::TakerOrMaker),
+            (EscrowAction::PublicCancelSrc, false, CallerRole::AccessToken),
+        ]
+    );
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400));
+    assert_eq!(
+        query_available_actions(&app, &contract_addr),
+        vec![
+            (EscrowAction::CancelSrc, false, CallerRole::TakerOrMaker),
+            (EscrowAction::PublicCancelSrc, false, CallerRole::AccessToken),
+            (EscrowAction::KeeperCancel, false, CallerRole::Anyone),
+            (EscrowAction::Rescue, false, CallerRole::Taker),
+            (EscrowAction::SweepExpired, false, CallerRole::Anyone),
+        ]
+    );
+
+    // Nothing remains available once the escrow is completed
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::CancelSrc { escrow_id: 1 },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_available_actions(&app, &contract_addr), vec![]);
+}
+
+#[test]
+fn test_available_actions_across_destination_stages() {
+    let mut app = mock_app();
+    let msg = test_instantiate_msg(EscrowType::Destination);
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    assert_eq!(
+        query_available_actions(&app, &contract_addr),
+        vec![(EscrowAction::WithdrawDst, true, CallerRole::Taker)]
+    );
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600));
+    assert_eq!(
+        query_available_actions(&app, &contract_addr),
+        vec![
+            (EscrowAction::WithdrawDst, true, CallerRole::Taker),
+            (EscrowAction::PublicWithdrawDst, false, CallerRole::AccessTokenOrMakerWithSecret),
+        ]
+    );
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    assert_eq!(
+        query_available_actions(&app, &contract_addr),
+        vec![(EscrowAction::CancelDst, false, CallerRole::TakerOrMaker)]
+    );
+}
+
+#[test]
+fn test_created_at_height_recorded() {
+    let mut app = mock_app();
+    app.update_block(|block| block.height += 42);
+    let creation_height = app.block_info().height;
+
+    let msg = test_instantiate_msg(EscrowType::Source);
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    app.update_block(|block| block.height += 10);
+    let escrow = query_escrow(&app, &contract_addr);
+    assert_eq!(escrow.created_at_height, creation_height);
+
+    let escrows: EscrowsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &contract_addr,
+            &QueryMsg::EscrowsByOrderHash { order_hash: msg.order_hash.clone() },
+        )
+        .unwrap();
+    assert_eq!(escrows.escrows[0].created_at_height, creation_height);
+}
+
+#[test]
+fn test_wrong_escrow_type_rejected() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr,
+            &ExecuteMsg::WithdrawDst { escrow_id: 1, secret },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::WrongEscrowType {
+            expected: EscrowType::Destination,
+            found: EscrowType::Source,
+        }
+    );
+}
+
+#[test]
+fn test_cw20_send_funds_escrow_principal() {
+    let mut app = mock_app();
+    let token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin { address: "owner".to_string(), amount: Uint128::new(5000) }],
+    );
+    let other_token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin { address: "owner".to_string(), amount: Uint128::new(5000) }],
+    );
+
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        token: token.to_string(),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(100, "uatom")]);
+    let hook = to_json_binary(&Cw20HookMsg::DeployEscrow { escrow_id: 1 }).unwrap();
+
+    // A different token contract can't fund the escrow
+    let err = app
+        .execute_contract(
+            Addr::unchecked("owner"),
+            other_token,
+            &Cw20ExecuteMsg::Send {
+                contract: contract_addr.to_string(),
+                amount: Uint128::new(1000),
+                msg: hook.clone(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTokenAddress { .. }
+    ));
+
+    // The amount must match the escrowed principal
+    let result = app.execute_contract(
+        Addr::unchecked("owner"),
+        token.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: contract_addr.to_string(),
+            amount: Uint128::new(999),
+            msg: hook.clone(),
+        },
+        &[],
+    );
+    assert!(result.is_err());
+
+    app.execute_contract(
+        Addr::unchecked("owner"),
+        token.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: contract_addr.to_string(),
+            amount: Uint128::new(1000),
+            msg: hook.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_cw20_balance(&app, &token, contract_addr.as_str()), Uint128::new(1000));
+
+    // A second delivery is rejected
+    let result = app.execute_contract(
+        Addr::unchecked("owner"),
+        token.clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: contract_addr.to_string(),
+            amount: Uint128::new(1000),
+            msg: hook,
+        },
+        &[],
+    );
+    assert!(result.is_err());
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_cw20_balance(&app, &token, "taker"), Uint128::new(1000));
+    assert_eq!(query_cw20_balance(&app, &token, contract_addr.as_str()), Uint128::zero());
+}
+
+#[test]
+fn test_cw20_escrow_unfunded_until_receive_hook() {
+    let mut app = mock_app();
+    let token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin { address: "owner".to_string(), amount: Uint128::new(5000) }],
+    );
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        token: token.to_string(),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(100, "uatom")]);
+    let escrow = query_escrow(&app, &contract_addr);
+    assert!(!escrow.principal_funded);
+    assert_eq!(escrow.balance, Uint128::zero());
+
+    // A plain transfer lands in the contract but doesn't fund the escrow
+    app.execute_contract(
+        Addr::unchecked("owner"),
+        token.clone(),
+        &Cw20ExecuteMsg::Transfer { recipient: contract_addr.to_string(), amount: Uint128::new(1000) },
+        &[],
+    )
+    .unwrap();
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    let withdraw = ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None };
+    let err = app
+        .execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &withdraw, &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::PrincipalNotFunded { escrow_id: 1 });
+    assert!(!query_action_preview(&app, &contract_addr, EscrowAction::WithdrawSrc).permitted);
+
+    // The hook credits exactly the principal, once
+    fund_cw20_escrow(&mut app, &token, &contract_addr, "owner", 1000);
+    let escrow = query_escrow(&app, &contract_addr);
+    assert!(escrow.principal_funded);
+    assert_eq!(escrow.balance, Uint128::new(1000));
+    let err = app
+        .execute_contract(
+            Addr::unchecked("owner"),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contract_addr.to_string(),
+                amount: Uint128::new(1000),
+                msg: to_json_binary(&Cw20HookMsg::DeployEscrow { escrow_id: 1 }).unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAmount { amount: "principal already funded with 1000".to_string() }
+    );
+
+    app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &withdraw, &[])
+        .unwrap();
+    assert_eq!(query_cw20_balance(&app, &token, "taker"), Uint128::new(1000));
+}
+
+#[test]
+fn test_ibc_denom_escrow_funding_and_withdrawal() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        native_denom: Some(IBC_DENOM.to_string()),
+        ibc_memo: Some("{\"forward\":{\"channel\":\"channel-0\"}}".to_string()),
+        ..test_instantiate_msg(EscrowType::Destination)
+    };
+
+    // Funding in the default denom is rejected
+    let contract_id = app.store_code(escrow_contract());
+    let result = app.instantiate_contract(
+        contract_id,
+        Addr::unchecked("owner"),
+        &msg,
+        &[Coin::new(1100, "uatom")],
+        "Escrow",
+        None,
+    );
+    assert!(result.is_err());
+
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, IBC_DENOM)]);
+    let escrow = query_escrow(&app, &contract_addr);
+    assert_eq!(escrow.ibc_memo, msg.ibc_memo);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawDst { escrow_id: 1, secret },
+        &[],
+    )
+    .unwrap();
+
+    // Principal to the maker, safety deposit back to the taker, both in the IBC denom
+    let maker_balance = app.wrap().query_balance("maker", IBC_DENOM).unwrap();
+    assert_eq!(maker_balance.amount, Uint128::new(1000));
+    let taker_balance = app.wrap().query_balance("taker", IBC_DENOM).unwrap();
+    assert_eq!(taker_balance.amount, Uint128::new(100));
+    let contract_balance = app.wrap().query_balance(contract_addr, IBC_DENOM).unwrap();
+    assert_eq!(contract_balance.amount, Uint128::zero());
+}
+
+#[test]
+fn test_malformed_denom_rejected() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    for denom in ["ibc/abc", "ibc/27394fb092d2eccd56123c74f36e4c1f926001ceada9ca97ea622b25f41e5eb2", "1atom", "u"] {
+        let msg = InstantiateMsg {
+            native_denom: Some(denom.to_string()),
+            ..test_instantiate_msg(EscrowType::Source)
+        };
+        let err = app
+            .instantiate_contract(
+                contract_id,
+                Addr::unchecked("owner"),
+                &msg,
+                &[Coin::new(1100, "uatom")],
+                "Escrow",
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidDenom { denom: denom.to_string() }
+        );
+    }
+}
+
+#[test]
+fn test_admin_rescue_after_delay() {
+    let mut app = mock_app();
+    let msg = test_instantiate_msg(EscrowType::Source);
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+
+    // Still gated after the taker's rescue delay
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400));
+    let err = app
+        .execute_contract(
+            Addr::unchecked("owner"),
+            contract_addr.clone(),
+            &ExecuteMsg::AdminRescue { escrow_id: 1, recipient: "recovery".to_string() },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TimelockNotExpired {
+            stage: "Admin rescue delay not expired".to_string(),
+            required_at: deployed_at.seconds() + 604800,
+            current: deployed_at.seconds() + 86400,
+        }
+    );
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(604800));
+
+    // Only the owner can sweep
+    let err = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr.clone(),
+            &ExecuteMsg::AdminRescue { escrow_id: 1, recipient: "taker".to_string() },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized { .. }
+    ));
+
+    app.execute_contract(
+        Addr::unchecked("owner"),
+        contract_addr.clone(),
+        &ExecuteMsg::AdminRescue { escrow_id: 1, recipient: "recovery".to_string() },
+        &[],
+    )
+    .unwrap();
+
+    let recovered = app.wrap().query_balance("recovery", "uatom").unwrap();
+    assert_eq!(recovered.amount, Uint128::new(1100));
+    let escrow = query_escrow(&app, &contract_addr);
+    assert_eq!(escrow.status, EscrowStatus::AdminRescued);
+
+    // The taker can no longer rescue a swept escrow
+    let result = app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr,
+        &ExecuteMsg::Rescue { escrow_id: 1 },
+        &[],
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_admin_rescue_delay_must_exceed_rescue_delay() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+    let msg = InstantiateMsg {
+        admin_rescue_delay: 86400,
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let err = app
+        .instantiate_contract(
+            contract_id,
+            Addr::unchecked("owner"),
+            &msg,
+            &[Coin::new(1100, "uatom")],
+            "Escrow",
+            None,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTime { .. }
+    ));
+}
+
+#[test]
+fn test_immutables_reject_malformed_hashes() {
+    let valid = Immutables {
+        order_hash: TEST_ORDER_HASH.to_string(),
+        hashlock: TEST_HASHLOCK.to_string(),
+        maker: Addr::unchecked("maker"),
+        taker: Addr::unchecked("taker"),
+        token: AssetKind::Native("uatom".to_string()),
+        amount: Uint128::new(1000),
+        safety_deposit: Uint128::new(100),
+        timelocks: create_test_timelocks(),
+    };
+    assert!(valid.validate().is_ok());
+
+    let malformed = [
+        TEST_HASHLOCK[..62].to_string(),                  // too short
+        format!("{}zz", &TEST_HASHLOCK[..62]),            // non-hex
+        TEST_HASHLOCK.to_uppercase(),                     // uppercase
+        format!("0x{}", &TEST_HASHLOCK[..62]),            // prefixed
+    ];
+
+    for value in malformed {
+        let immutables = Immutables { hashlock: value.clone(), ..valid.clone() };
+        let err = immutables.validate().unwrap_err();
+        assert!(err.to_string().contains("Hashlock"), "{value}: {err}");
+
+        let immutables = Immutables { order_hash: value.clone(), ..valid.clone() };
+        let err = immutables.validate().unwrap_err();
+        assert!(err.to_string().contains("Order hash"), "{value}: {err}");
+    }
+}
+
+fn query_completed_escrows(app: &App, contract_addr: &Addr, status: Option<EscrowStatus>) -> CompletedEscrowsResponse {
+    app.wrap()
+        .query_wasm_smart(
+            contract_addr,
+            &QueryMsg::CompletedEscrows { start_after: None, limit: None, status },
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_completed_escrows_filtered_by_status() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let withdrawn_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let cancelled_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    // Active escrows aren't listed
+    assert!(query_completed_escrows(&app, &withdrawn_addr, None).escrows.is_empty());
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| {
+        block.time = deployed_at.plus_seconds(3600);
+        block.height += 5;
+    });
+    let withdrawn_height = app.block_info().height;
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        withdrawn_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+
+    app.update_block(|block| {
+        block.time = deployed_at.plus_seconds(3 * 3600);
+        block.height += 5;
+    });
+    let cancelled_height = app.block_info().height;
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        cancelled_addr.clone(),
+        &ExecuteMsg::CancelSrc { escrow_id: 1 },
+        &[],
+    )
+    .unwrap();
+
+    let completed = query_completed_escrows(&app, &withdrawn_addr, None);
+    assert_eq!(
+        completed.escrows,
+        vec![CompletedEscrow {
+            escrow_id: 1,
+            order_hash: msg.order_hash.clone(),
+            status: EscrowStatus::Withdrawn,
+            completed_at_height: Some(withdrawn_height),
+        }]
+    );
+    assert!(query_completed_escrows(&app, &withdrawn_addr, Some(EscrowStatus::Cancelled)).escrows.is_empty());
+
+    let completed = query_completed_escrows(&app, &cancelled_addr, Some(EscrowStatus::Cancelled));
+    assert_eq!(completed.escrows.len(), 1);
+    assert_eq!(completed.escrows[0].completed_at_height, Some(cancelled_height));
+    assert!(query_completed_escrows(&app, &cancelled_addr, Some(EscrowStatus::Withdrawn)).escrows.is_empty());
+
+    // Paging past the last id returns nothing
+    let completed: CompletedEscrowsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &cancelled_addr,
+            &QueryMsg::CompletedEscrows { start_after: Some(1), limit: Some(10), status: None },
+        )
+        .unwrap();
+    assert!(completed.escrows.is_empty());
+}
+
+#[test]
+fn test_escrow_id_exhaustion() {
+    let mut storage = cosmwasm_std::testing::MockStorage::new();
+    ESCROW_COUNTER.save(&mut storage, &(u64::MAX - 1)).unwrap();
+
+    assert_eq!(get_next_escrow_id(&mut storage).unwrap(), u64::MAX);
+    assert_eq!(
+        get_next_escrow_id(&mut storage).unwrap_err(),
+        ContractError::OperationFailed { reason: "escrow id space exhausted".to_string() }
+    );
+    // The counter is left untouched
+    assert_eq!(ESCROW_COUNTER.load(&storage).unwrap(), u64::MAX);
+}
+
+#[test]
+fn test_hashlock_for_round_trip() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+
+    let secret = generate_secret();
+    let response: HashlockResponse = app
+        .wrap()
+        .query_wasm_smart(&contract_addr, &QueryMsg::HashlockFor { secret: secret.clone() })
+        .unwrap();
+    assert_eq!(response.hashlock, hash_secret(&secret));
+
+    let msg = InstantiateMsg {
+        hashlock: response.hashlock,
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Withdrawn);
+}
+
+#[test]
+fn test_safety_deposit_in_separate_denom() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        safety_deposit_denom: Some("uosmo".to_string()),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+
+    // Both denoms have to be covered
+    for funds in [
+        vec![Coin::new(1100, "uatom")],
+        vec![Coin::new(1000, "uatom")],
+        vec![Coin::new(1000, "uatom"), Coin::new(99, "uosmo")],
+    ] {
+        let result = app.instantiate_contract(
+            contract_id,
+            Addr::unchecked("owner"),
+            &msg,
+            &funds,
+            "Escrow",
+            None,
+        );
+        assert!(result.is_err());
+    }
+
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &msg,
+        &[Coin::new(1000, "uatom"), Coin::new(100, "uosmo")],
+    );
+    assert_eq!(query_escrow(&app, &contract_addr).safety_deposit_denom, "uosmo");
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+
+    let taker_atom = app.wrap().query_balance("taker", "uatom").unwrap();
+    assert_eq!(taker_atom.amount, Uint128::new(2000 + 1000));
+    let taker_osmo = app.wrap().query_balance("taker", "uosmo").unwrap();
+    assert_eq!(taker_osmo.amount, Uint128::new(100));
+    let contract_osmo = app.wrap().query_balance(contract_addr, "uosmo").unwrap();
+    assert_eq!(contract_osmo.amount, Uint128::zero());
+}
+
+fn test_signing_key(seed: u8) -> (SigningKey, Binary) {
+    let signing_key = SigningKey::from_bytes(&[seed; 32].into()).unwrap();
+    let pubkey = signing_key.verifying_key().to_encoded_point(true).as_bytes().to_vec();
+    (signing_key, Binary::from(pubkey))
+}
+
+fn sign_cancellation(signing_key: &SigningKey, contract_addr: &Addr, order_hash: &str) -> Binary {
+    let message_hash = cancel_authorization_hash(contract_addr, 1, order_hash);
+    let signature: Signature = signing_key.sign_prehash(&message_hash).unwrap();
+    Binary::from(signature.to_bytes().to_vec())
+}
+
+#[test]
+fn test_cancel_src_signed_by_maker() {
+    let mut app = mock_app();
+    let (maker_key, maker_pubkey) = test_signing_key(7);
+    let (other_key, other_pubkey) = test_signing_key(9);
+    let msg = InstantiateMsg {
+        maker_pubkey: Some(maker_pubkey.clone()),
+        public_caller_reward: Uint128::new(40),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let maker_sig = sign_cancellation(&maker_key, &contract_addr, &msg.order_hash);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+
+    // A key other than the registered one is rejected
+    let err = app
+        .execute_contract(
+            Addr::unchecked("relayer"),
+            contract_addr.clone(),
+            &ExecuteMsg::CancelSrcSigned {
+                escrow_id: 1,
+                maker_sig: sign_cancellation(&other_key, &contract_addr, &msg.order_hash),
+                maker_pubkey: other_pubkey,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSignature {});
+
+    // A signature over a different message is rejected
+    let err = app
+        .execute_contract(
+            Addr::unchecked("relayer"),
+            contract_addr.clone(),
+            &ExecuteMsg::CancelSrcSigned {
+                escrow_id: 1,
+                maker_sig: sign_cancellation(&maker_key, &Addr::unchecked("other_contract"), &msg.order_hash),
+                maker_pubkey: maker_pubkey.clone(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSignature {});
+
+    app.execute_contract(
+        Addr::unchecked("relayer"),
+        contract_addr.clone(),
+        &ExecuteMsg::CancelSrcSigned { escrow_id: 1, maker_sig, maker_pubkey },
+        &[],
+    )
+    .unwrap();
+
+    // Principal refunded to the maker, the relayer earns the capped reward and
+    // the creator gets the rest of its deposit back
+    let maker_balance = app.wrap().query_balance("maker", "uatom").unwrap();
+    assert_eq!(maker_balance.amount, Uint128::new(2000 + 1000));
+    let relayer_balance = app.wrap().query_balance("relayer", "uatom").unwrap();
+    assert_eq!(relayer_balance.amount, Uint128::new(40));
+    let creator_balance = app.wrap().query_balance("owner", "uatom").unwrap();
+    assert_eq!(creator_balance.amount, Uint128::new(10000 - 1100 + 60));
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Cancelled);
+}
+
+#[test]
+fn test_cancel_src_signed_waits_for_public_cancellation() {
+    let mut app = mock_app();
+    let (maker_key, maker_pubkey) = test_signing_key(7);
+    let msg = InstantiateMsg {
+        maker_pubkey: Some(maker_pubkey.clone()),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let maker_sig = sign_cancellation(&maker_key, &contract_addr, &msg.order_hash);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked("relayer"),
+            contract_addr,
+            &ExecuteMsg::CancelSrcSigned { escrow_id: 1, maker_sig, maker_pubkey },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TimelockNotExpired {
+            stage: "SrcPublicCancellation".to_string(),
+            required_at: deployed_at.seconds() + 4 * 3600,
+            current: deployed_at.seconds() + 3 * 3600,
+        }
+    );
+}
+
+#[test]
+fn test_escrow_response_unpacked_timelocks() {
+    let mut app = mock_app();
+    let msg = test_instantiate_msg(EscrowType::Source);
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let escrow = query_escrow(&app, &contract_addr);
+    let expected = create_test_timelocks();
+    let unpacked = escrow.unpacked_timelocks;
+    assert_eq!(unpacked.deployed_at, app.block_info().time.seconds() as u32);
+    assert_eq!(unpacked.src_withdrawal, expected.get(TimelockStage::SrcWithdrawal));
+    assert_eq!(unpacked.src_public_withdrawal, expected.get(TimelockStage::SrcPublicWithdrawal));
+    assert_eq!(unpacked.src_cancellation, expected.get(TimelockStage::SrcCancellation));
+    assert_eq!(unpacked.src_public_cancellation, expected.get(TimelockStage::SrcPublicCancellation));
+    assert_eq!(unpacked.dst_withdrawal, expected.get(TimelockStage::DstWithdrawal));
+    assert_eq!(unpacked.dst_public_withdrawal, expected.get(TimelockStage::DstPublicWithdrawal));
+    assert_eq!(unpacked.dst_cancellation, expected.get(TimelockStage::DstCancellation));
+
+    // Packed form is still returned unchanged
+    assert_eq!(escrow.immutables.timelocks.unpack(), unpacked);
+}
+
+#[test]
+fn test_min_safety_deposit_ratio() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    // 1% of a 1000 principal: 10 is exactly at the boundary
+    assert_eq!(MIN_SAFETY_DEPOSIT_BPS, 100);
+    let msg = InstantiateMsg {
+        safety_deposit: Uint128::new(10),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    instantiate_escrow(&mut app, &msg, &[Coin::new(1010, "uatom")]);
+
+    let msg = InstantiateMsg {
+        safety_deposit: Uint128::new(9),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let err = app
+        .instantiate_contract(
+            contract_id,
+            Addr::unchecked("owner"),
+            &msg,
+            &[Coin::new(1009, "uatom")],
+            "Escrow",
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAmount { 
+            amount: "safety deposit 9 is below minimum 10 (100 bps of 1000)".to_string() 
+        }
+    );
+}
+
+#[test]
+fn test_native_principal_asset_kind() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg {
+        native_denom: Some("uosmo".to_string()),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uosmo")]);
+
+    let escrow = query_escrow(&app, &contract_addr);
+    assert_eq!(escrow.immutables.token, AssetKind::Native("uosmo".to_string()));
+}
+
+#[test]
+fn test_cw20_principal_asset_kind() {
+    let mut app = mock_app();
+    let token = instantiate_cw20(&mut app, vec![]);
+    let msg = InstantiateMsg {
+        token: token.to_string(),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(100, "uatom")]);
+
+    let escrow = query_escrow(&app, &contract_addr);
+    assert_eq!(escrow.immutables.token, AssetKind::Cw20(token));
+}
+
+#[test]
+fn test_validate_params_dry_run() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+
+    let response: ValidateParamsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &contract_addr,
+            &QueryMsg::ValidateParams { params: Box::new(test_instantiate_msg(EscrowType::Destination)) },
+        )
+        .unwrap();
+    assert!(response.valid);
+    assert!(response.errors.is_empty());
+
+    // Several independent problems are all reported
+    let params = InstantiateMsg {
+        hashlock: "not_a_hash".to_string(),
+        amount: Uint128::zero(),
+        rescue_delay_override: Some(60),
+        native_denom: Some("ibc/abc".to_string()),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let response: ValidateParamsResponse = app
+        .wrap()
+        .query_wasm_smart(&contract_addr, &QueryMsg::ValidateParams { params: Box::new(params.clone()) })
+        .unwrap();
+    assert!(!response.valid);
+    assert_eq!(response.errors.len(), 3);
+    assert!(response.errors.iter().any(|err| err.contains("Invalid denom: ibc/abc")));
+    assert!(response.errors.iter().any(|err| err.contains("Hashlock")));
+    assert!(response.errors.iter().any(|err| err.contains("Rescue delay 60 is below minimum")));
+
+    // Creation rejects the same parameters with the first reported error
+    let contract_id = app.store_code(escrow_contract());
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &params, &[], "Escrow", None)
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap().to_string(), response.errors[0]);
+}
+
+#[test]
+fn test_effective_phase_follows_block_time() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+
+    let expected = [
+        (0, EscrowPhase::Pending),
+        (3600, EscrowPhase::WithdrawOpen),
+        (2 * 3600, EscrowPhase::PublicWithdrawOpen),
+        (3 * 3600, EscrowPhase::CancelOpen),
+        (4 * 3600, EscrowPhase::PublicCancelOpen),
+        (86400, EscrowPhase::RescueOpen),
+    ];
+    for (offset, phase) in expected {
+        app.update_block(|block| block.time = deployed_at.plus_seconds(offset));
+        assert_eq!(query_escrow(&app, &contract_addr).effective_phase, phase, "at +{}s", offset);
+    }
+
+    // The escrows-by-order-hash listing reports the same phase
+    let escrows: EscrowsResponse = app
+        .wrap()
+        .query_wasm_smart(&contract_addr, &QueryMsg::EscrowsByOrderHash { order_hash: msg.order_hash.clone() })
+        .unwrap();
+    assert_eq!(escrows.escrows[0].effective_phase, EscrowPhase::RescueOpen);
+
+    // A completed escrow is finalized regardless of time
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).effective_phase, EscrowPhase::Finalized);
+}
+
+#[test]
+fn test_rescue_allowlist_leaves_spam_denoms() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+    app.send_tokens(
+        Addr::unchecked("owner"),
+        contract_addr.clone(),
+        &[Coin::new(300, "uosmo"), Coin::new(200, IBC_DENOM)],
+    )
+    .unwrap();
+
+    let allowlist_msg = ExecuteMsg::SetRescueAllowlist { denoms: Some(vec!["uosmo".to_string()]) };
+    let err = app
+        .execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &allowlist_msg, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized { .. }));
+    app.execute_contract(Addr::unchecked("owner"), contract_addr.clone(), &allowlist_msg, &[])
+        .unwrap();
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400));
+    app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &ExecuteMsg::Rescue { escrow_id: 1 }, &[])
+        .unwrap();
+
+    // Principal, deposit and the allowlisted denom go to the taker
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(3100));
+    assert_eq!(app.wrap().query_balance("taker", "uosmo").unwrap().amount, Uint128::new(300));
+    assert_eq!(app.wrap().query_balance("taker", IBC_DENOM).unwrap().amount, Uint128::zero());
+    assert_eq!(app.wrap().query_balance(&contract_addr, IBC_DENOM).unwrap().amount, Uint128::new(200));
+}
+
+#[test]
+fn test_rescue_without_allowlist_sweeps_stray_denoms() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+    app.send_tokens(Addr::unchecked("owner"), contract_addr.clone(), &[Coin::new(200, IBC_DENOM)])
+        .unwrap();
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400));
+    app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &ExecuteMsg::Rescue { escrow_id: 1 }, &[])
+        .unwrap();
+
+    assert_eq!(app.wrap().query_balance("taker", IBC_DENOM).unwrap().amount, Uint128::new(200));
+    assert!(app.wrap().query_all_balances(&contract_addr).unwrap().is_empty());
+}
+
+fn query_ownership(app: &App, contract_addr: &Addr) -> OwnershipResponse {
+    app.wrap().query_wasm_smart(contract_addr, &QueryMsg::Ownership {}).unwrap()
+}
+
+#[test]
+fn test_two_step_ownership_transfer() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+
+    // Only the owner can nominate a successor
+    let propose_msg = ExecuteMsg::ProposeNewOwner { owner: "new_owner".to_string() };
+    let err = app
+        .execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &propose_msg, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized { .. }));
+    app.execute_contract(Addr::unchecked("owner"), contract_addr.clone(), &propose_msg, &[])
+        .unwrap();
+
+    // Ownership doesn't move until the nominee accepts
+    let ownership = query_ownership(&app, &contract_addr);
+    assert_eq!(ownership.owner, Addr::unchecked("owner"));
+    assert_eq!(ownership.pending_owner, Some(Addr::unchecked("new_owner")));
+
+    app.execute_contract(Addr::unchecked("new_owner"), contract_addr.clone(), &ExecuteMsg::AcceptOwnership {}, &[])
+        .unwrap();
+    let ownership = query_ownership(&app, &contract_addr);
+    assert_eq!(ownership.owner, Addr::unchecked("new_owner"));
+    assert_eq!(ownership.pending_owner, None);
+
+    // The new owner holds the admin rights, the previous one lost them
+    let allowlist_msg = ExecuteMsg::SetRescueAllowlist { denoms: None };
+    let err = app
+        .execute_contract(Addr::unchecked("owner"), contract_addr.clone(), &allowlist_msg, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized { .. }));
+    app.execute_contract(Addr::unchecked("new_owner"), contract_addr.clone(), &allowlist_msg, &[])
+        .unwrap();
+}
+
+#[test]
+fn test_accept_ownership_rejects_other_callers() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+
+    // Nothing to accept before a proposal
+    let err = app
+        .execute_contract(Addr::unchecked("new_owner"), contract_addr.clone(), &ExecuteMsg::AcceptOwnership {}, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized { .. }));
+
+    app.execute_contract(
+        Addr::unchecked("owner"),
+        contract_addr.clone(),
+        &ExecuteMsg::ProposeNewOwner { owner: "new_owner".to_string() },
+        &[],
+    )
+    .unwrap();
+
+    // Neither a third party nor the current owner can complete the transfer
+    for caller in ["taker", "owner"] {
+        let err = app
+            .execute_contract(Addr::unchecked(caller), contract_addr.clone(), &ExecuteMsg::AcceptOwnership {}, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized { .. }));
+    }
+    let ownership = query_ownership(&app, &contract_addr);
+    assert_eq!(ownership.owner, Addr::unchecked("owner"));
+    assert_eq!(ownership.pending_owner, Some(Addr::unchecked("new_owner")));
+}
+
+#[test]
+fn test_propose_new_owner_overwrites_pending_proposal() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+
+    for nominee in ["wrong_owner", "new_owner"] {
+        app.execute_contract(
+            Addr::unchecked("owner"),
+            contract_addr.clone(),
+            &ExecuteMsg::ProposeNewOwner { owner: nominee.to_string() },
+            &[],
+        )
+        .unwrap();
+    }
+    assert_eq!(query_ownership(&app, &contract_addr).pending_owner, Some(Addr::unchecked("new_owner")));
+
+    // The replaced nominee can no longer accept
+    let err = app
+        .execute_contract(Addr::unchecked("wrong_owner"), contract_addr.clone(), &ExecuteMsg::AcceptOwnership {}, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized { .. }));
+
+    app.execute_contract(Addr::unchecked("new_owner"), contract_addr.clone(), &ExecuteMsg::AcceptOwnership {}, &[])
+        .unwrap();
+    assert_eq!(query_ownership(&app, &contract_addr).owner, Addr::unchecked("new_owner"));
+}
+
+#[test]
+fn test_rescue_status_query() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+    let deployed_at = app.block_info().time;
+    let query = QueryMsg::RescueStatus { escrow_id: 1 };
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400 - 100));
+    let status: RescueStatusResponse = app.wrap().query_wasm_smart(&contract_addr, &query).unwrap();
+    assert_eq!(
+        status,
+        RescueStatusResponse {
+            rescue_start: deployed_at.seconds() + 86400,
+            rescue_available: false,
+            seconds_remaining: 100,
+        }
+    );
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400));
+    let status: RescueStatusResponse = app.wrap().query_wasm_smart(&contract_addr, &query).unwrap();
+    assert!(status.rescue_available);
+    assert_eq!(status.seconds_remaining, 0);
+
+    // The status agrees with what the rescue handler enforces
+    app.execute_contract(Addr::unchecked("taker"), contract_addr, &ExecuteMsg::Rescue { escrow_id: 1 }, &[])
+        .unwrap();
+}
+
+#[test]
+fn test_creation_rejected_past_u32_timestamps() {
+    let mut app = mock_app();
+    app.update_block(|block| block.time = cosmwasm_std::Timestamp::from_seconds(u32::MAX as u64 + 1));
+    let contract_id = app.store_code(escrow_contract());
+
+    let err = app
+        .instantiate_contract(
+            contract_id,
+            Addr::unchecked("owner"),
+            &test_instantiate_msg(EscrowType::Source),
+            &[Coin::new(1100, "uatom")],
+            "Escrow",
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidCreationTime {});
+}
+
+#[test]
+fn test_cw721_escrow_locks_and_withdraws_nft() {
+    let mut app = mock_app();
+    let nft = instantiate_cw721(&mut app, "maker", "punk-1");
+
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        token: nft.to_string(),
+        token_id: Some("punk-1".to_string()),
+        amount: Uint128::one(),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+
+    // An NFT escrow holds exactly one token
+    let contract_id = app.store_code(escrow_contract());
+    let err = app
+        .instantiate_contract(
+            contract_id,
+            Addr::unchecked("owner"),
+            &InstantiateMsg { amount: Uint128::new(2), ..msg.clone() },
+            &[Coin::new(100, "uatom")],
+            "Escrow",
+            None,
+        )
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidAmount { .. }));
+
+    // Only the safety deposit is paid in native funds
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(100, "uatom")]);
+    assert_eq!(
+        query_escrow(&app, &contract_addr).immutables.token,
+        AssetKind::Cw721 { contract: nft.clone(), token_id: "punk-1".to_string() }
+    );
+
+    // The maker lets an approved operator deliver the NFT on its behalf
+    let approve: Cw721BaseExecuteMsg = cw721_base::ExecuteMsg::Approve {
+        spender: "relayer".to_string(),
+        token_id: "punk-1".to_string(),
+        expires: None,
+    };
+    app.execute_contract(Addr::unchecked("maker"), nft.clone(), &approve, &[])
+        .unwrap();
+    let send: Cw721BaseExecuteMsg = cw721_base::ExecuteMsg::SendNft {
+        contract: contract_addr.to_string(),
+        token_id: "punk-1".to_string(),
+        msg: to_json_binary(&Cw20HookMsg::DeployEscrow { escrow_id: 1 }).unwrap(),
+    };
+    app.execute_contract(Addr::unchecked("relayer"), nft.clone(), &send, &[])
+        .unwrap();
+    let locked = query_nft_owner(&app, &nft, "punk-1");
+    assert_eq!(locked.owner, contract_addr.to_string());
+    assert!(locked.approvals.is_empty());
+    assert!(query_escrow(&app, &contract_addr).principal_funded);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(query_nft_owner(&app, &nft, "punk-1").owner, "taker");
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Withdrawn);
+}
+
+#[test]
+fn test_cw721_funding_rejects_other_nft() {
+    let mut app = mock_app();
+    let nft = instantiate_cw721(&mut app, "maker", "punk-1");
+    let other_nft = instantiate_cw721(&mut app, "maker", "punk-1");
+
+    let msg = InstantiateMsg {
+        token: nft.to_string(),
+        token_id: Some("punk-1".to_string()),
+        amount: Uint128::one(),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(100, "uatom")]);
+
+    let send: Cw721BaseExecuteMsg = cw721_base::ExecuteMsg::SendNft {
+        contract: contract_addr.to_string(),
+        token_id: "punk-1".to_string(),
+        msg: to_json_binary(&Cw20HookMsg::DeployEscrow { escrow_id: 1 }).unwrap(),
+    };
+    let err = app
+        .execute_contract(Addr::unchecked("maker"), other_nft, &send, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidTokenAddress { .. }));
+}
+
+#[test]
+fn test_short_secret_rejected_even_when_hash_matches() {
+    let mut app = mock_app();
+    let secret = "weak".to_string();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    let withdraw_msg = ExecuteMsg::WithdrawSrc { escrow_id: 1, secret: secret.clone(), recipient: None };
+    let err = app
+        .execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &withdraw_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SecretTooShort { length: 4, min: 32 }
+    );
+}
+
+#[test]
+fn test_min_secret_bytes_below_default_rejected() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    // The creator can't opt out of the floor to admit a weak preimage
+    let msg = InstantiateMsg {
+        min_secret_bytes: Some(0),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &msg, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SecretTooShort { length: 0, min: 32 }
+    );
+
+    // Raising it is fine, and then binds withdrawals
+    let secret = "s".repeat(40);
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        min_secret_bytes: Some(48),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let config: ConfigResponse = app.wrap().query_wasm_smart(&contract_addr, &QueryMsg::Config {}).unwrap();
+    assert_eq!(config.min_secret_bytes, 48);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    let err = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr,
+            &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SecretTooShort { length: 40, min: 48 }
+    );
+}
+
+#[test]
+fn test_sweep_expired_source_refunds_maker_and_rewards_keeper() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+    let deployed_at = app.block_info().time;
+    let sweep_msg = ExecuteMsg::SweepExpired { escrow_id: 1 };
+
+    // Public cancellation is open but the rescue delay hasn't passed
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+    let err = app
+        .execute_contract(Addr::unchecked("keeper"), contract_addr.clone(), &sweep_msg, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::TimelockNotExpired { .. }));
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400));
+    app.execute_contract(Addr::unchecked("keeper"), contract_addr.clone(), &sweep_msg, &[])
+        .unwrap();
+
+    assert_eq!(app.wrap().query_balance("maker", "uatom").unwrap().amount, Uint128::new(3000));
+    assert_eq!(app.wrap().query_balance("keeper", "uatom").unwrap().amount, Uint128::new(100));
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Cancelled);
+
+    let err = app
+        .execute_contract(Addr::unchecked("keeper"), contract_addr, &sweep_msg, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::EscrowAlreadyCompleted { .. }));
+}
+
+#[test]
+fn test_sweep_expired_destination_refunds_taker() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Destination),
+        &[Coin::new(1100, "uatom")],
+    );
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400));
+    app.execute_contract(
+        Addr::unchecked("keeper"),
+        contract_addr,
+        &ExecuteMsg::SweepExpired { escrow_id: 1 },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(3000));
+    assert_eq!(app.wrap().query_balance("keeper", "uatom").unwrap().amount, Uint128::new(100));
+}
+
+fn sign_public_withdrawal(signing_key: &SigningKey, contract_addr: &Addr, relayer: &str) -> Binary {
+    let message_hash = public_withdraw_authorization_hash(contract_addr, 1, &Addr::unchecked(relayer));
+    let signature: Signature = signing_key.sign_prehash(&message_hash).unwrap();
+    Binary::from(signature.to_bytes().to_vec())
+}
+
+#[test]
+fn test_public_withdraw_src_authorized_by_maker_only() {
+    let mut app = mock_app();
+    let (maker_key, maker_pubkey) = test_signing_key(12);
+    let (taker_key, taker_pubkey) = test_signing_key(11);
+    let (other_key, _) = test_signing_key(13);
+    let msg = InstantiateMsg {
+        maker_pubkey: Some(maker_pubkey),
+        taker_pubkey: Some(taker_pubkey),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+
+    let authorized = |sig: Binary| ExecuteMsg::PublicWithdrawSrcAuthorized { escrow_id: 1, authorization_sig: sig };
+    let sig = sign_public_withdrawal(&maker_key, &contract_addr, "relayer");
+
+    // The public window hasn't opened yet
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    let err = app
+        .execute_contract(Addr::unchecked("relayer"), contract_addr.clone(), &authorized(sig.clone()), &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::TimelockNotExpired { .. }));
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600));
+
+    // The signature is bound to the relayer and to a registered key
+    let err = app
+        .execute_contract(Addr::unchecked("other_relayer"), contract_addr.clone(), &authorized(sig.clone()), &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSignature {});
+    let forged = sign_public_withdrawal(&other_key, &contract_addr, "relayer");
+    let err = app
+        .execute_contract(Addr::unchecked("relayer"), contract_addr.clone(), &authorized(forged), &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSignature {});
+
+    // The taker is paid by this withdrawal, so its own key can't authorize it
+    let self_authorized = sign_public_withdrawal(&taker_key, &contract_addr, "relayer");
+    let err = app
+        .execute_contract(Addr::unchecked("relayer"), contract_addr.clone(), &authorized(self_authorized), &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSignature {});
+
+    app.execute_contract(Addr::unchecked("relayer"), contract_addr.clone(), &authorized(sig), &[])
+        .unwrap();
+
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(3000));
+    assert_eq!(app.wrap().query_balance("relayer", "uatom").unwrap().amount, Uint128::new(100));
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Withdrawn);
+}
+
+#[test]
+fn test_non_default_native_denom_end_to_end() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        native_denom: Some("uosmo".to_string()),
+        creation_fee: Uint128::new(50),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+
+    // Funding must arrive in the configured denom
+    let contract_id = app.store_code(escrow_contract());
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &msg, &[Coin::new(1150, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidAmount { .. }));
+
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1150, "uosmo")]);
+    let config: ConfigResponse = app.wrap().query_wasm_smart(&contract_addr, &QueryMsg::Config {}).unwrap();
+    assert_eq!(config.native_denom, "uosmo");
+    assert_eq!(query_escrow(&app, &contract_addr).safety_deposit_denom, "uosmo");
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(app.wrap().query_balance("taker", "uosmo").unwrap().amount, Uint128::new(1100));
+
+    app.execute_contract(
+        Addr::unchecked("owner"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawFees { recipient: "treasury".to_string() },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(app.wrap().query_balance("treasury", "uosmo").unwrap().amount, Uint128::new(50));
+    assert!(app.wrap().query_all_balances(&contract_addr).unwrap().is_empty());
+}
+
+#[test]
+fn test_config_query_returns_contract_config() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+
+    let mut expected = ConfigResponse {
+        owner: Addr::unchecked("owner"),
+        access_token: AccessToken::Cw20(Addr::unchecked("access_token")),
+        rescue_delay: 86400,
+        factory: Addr::unchecked("owner"),
+        creation_fee: Uint128::zero(),
+        public_caller_reward: Uint128::new(100),
+        native_denom: "uatom".to_string(),
+        admin_rescue_delay: 604800,
+        rescue_denom_allowlist: None,
+        min_secret_bytes: 32,
+        public_authority_pubkey: None,
+        withdraw_fee: Uint128::zero(),
+        treasury: Addr::unchecked("owner"),
+        default_access_token_threshold: Uint128::zero(),
+        check_solvency: false,
+        event_prefix: String::new(),
+        max_timelock_extension_hours: 0,
+        timelock_skew_seconds: 0,
+        contract_version: cw2::ContractVersion {
+            contract: "crates.io:escrow-contract".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        },
+    };
+    let config: ConfigResponse = app.wrap().query_wasm_smart(&contract_addr, &QueryMsg::Config {}).unwrap();
+    assert_eq!(config, expected);
+
+    // Owner updates show up in the same response
+    for (sender, msg) in [
+        ("owner", ExecuteMsg::SetRescueAllowlist { denoms: Some(vec!["uosmo".to_string()]) }),
+        ("owner", ExecuteMsg::ProposeNewOwner { owner: "new_owner".to_string() }),
+        ("new_owner", ExecuteMsg::AcceptOwnership {}),
+    ] {
+        app.execute_contract(Addr::unchecked(sender), contract_addr.clone(), &msg, &[]).unwrap();
+    }
+    expected.rescue_denom_allowlist = Some(vec!["uosmo".to_string()]);
+    expected.owner = Addr::unchecked("new_owner");
+    let config: ConfigResponse = app.wrap().query_wasm_smart(&contract_addr, &QueryMsg::Config {}).unwrap();
+    assert_eq!(config, expected);
+}
+
+fn query_actionable_escrows(app: &App, contract_addr: &Addr) -> Vec<ActionableEscrow> {
+    let response: ActionableEscrowsResponse = app
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::ActionableEscrows { start_after: None, limit: None })
+        .unwrap();
+    response.escrows
+}
+
+#[test]
+fn test_actionable_escrows_reports_keeper_rewards() {
+    let mut app = mock_app();
+    let start = app.block_info().time;
+    let src_msg = test_instantiate_msg(EscrowType::Source);
+
+    // Escrows deployed at staggered times sit in different stages later on
+    let fresh = instantiate_escrow(&mut app, &src_msg, &[Coin::new(1100, "uatom")]);
+    let destination = instantiate_escrow(&mut app, &test_instantiate_msg(EscrowType::Destination), &[Coin::new(1100, "uatom")]);
+    let msg = InstantiateMsg { public_caller_reward: Uint128::new(40), ..src_msg.clone() };
+    let cancellable = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    app.update_block(|block| block.time = start.plus_seconds(3600));
+    assert!(query_actionable_escrows(&app, &fresh).is_empty());
+
+    app.update_block(|block| block.time = start.plus_seconds(2 * 3600));
+    assert_eq!(
+        query_actionable_escrows(&app, &destination),
+        vec![ActionableEscrow {
+            escrow_id: 1,
+            action: EscrowAction::PublicWithdrawDst,
+            reward: Coin::new(100, "uatom"),
+        }]
+    );
+
+    app.update_block(|block| block.time = start.plus_seconds(4 * 3600));
+    assert_eq!(
+        query_actionable_escrows(&app, &cancellable),
+        vec![ActionableEscrow {
+            escrow_id: 1,
+            action: EscrowAction::PublicCancelSrc,
+            reward: Coin::new(40, "uatom"),
+        }]
+    );
+
+    app.update_block(|block| block.time = start.plus_seconds(86400));
+    assert_eq!(query_actionable_escrows(&app, &fresh)[0].action, EscrowAction::SweepExpired);
+
+    // Completed escrows drop out
+    app.execute_contract(
+        Addr::unchecked("keeper"),
+        fresh.clone(),
+        &ExecuteMsg::SweepExpired { escrow_id: 1 },
+        &[],
+    )
+    .unwrap();
+    let response: ActionableEscrowsResponse = app
+        .wrap()
+        .query_wasm_smart(&fresh, &QueryMsg::ActionableEscrows { start_after: None, limit: None })
+        .unwrap();
+    assert_eq!(response, ActionableEscrowsResponse { escrows: vec![], last_scanned: Some(1) });
+}
+
+fn query_pending_cancellations(app: &App, contract_addr: &Addr) -> Vec<PendingCancellation> {
+    let response: PendingCancellationsResponse = app
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::PendingCancellations { start_after: None, limit: None })
+        .unwrap();
+    response.escrows
+}
+
+#[test]
+fn test_pending_cancellations_lists_escrows_past_cancellation() {
+    let mut app = mock_app();
+    let start = app.block_info().time;
+    let msg = InstantiateMsg { public_caller_reward: Uint128::new(40), ..test_instantiate_msg(EscrowType::Source) };
+    let src = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let dst = instantiate_escrow(&mut app, &test_instantiate_msg(EscrowType::Destination), &[Coin::new(1100, "uatom")]);
+
+    // Nothing is pending while withdrawal is still open
+    app.update_block(|block| block.time = start.plus_seconds(2 * 3600));
+    assert!(query_pending_cancellations(&app, &src).is_empty());
+    assert!(query_pending_cancellations(&app, &dst).is_empty());
+
+    // Both cancellation stages open at 3h, before a keeper may act on either
+    app.update_block(|block| block.time = start.plus_seconds(3 * 3600));
+    assert_eq!(
+        query_pending_cancellations(&app, &dst),
+        vec![PendingCancellation {
+            escrow_id: 1,
+            escrow_type: EscrowType::Destination,
+            cancellation_at: start.seconds() + 3 * 3600,
+            keeper_reward: None,
+        }]
+    );
+    let pending = PendingCancellation {
+        escrow_id: 1,
+        escrow_type: EscrowType::Source,
+        cancellation_at: start.seconds() + 3 * 3600,
+        keeper_reward: None,
+    };
+    assert_eq!(query_pending_cancellations(&app, &src), vec![pending.clone()]);
+
+    // Public cancellation alone is left to access token holders
+    app.update_block(|block| block.time = start.plus_seconds(4 * 3600));
+    assert_eq!(query_pending_cancellations(&app, &src), vec![pending.clone()]);
+
+    // Source escrows become keeper-cancellable once the rescue delay has passed too
+    app.update_block(|block| block.time = start.plus_seconds(86400));
+    assert_eq!(
+        query_pending_cancellations(&app, &src),
+        vec![PendingCancellation { keeper_reward: Some(Coin::new(40, "uatom")), ..pending }]
+    );
+
+    // Completed escrows drop out
+    app.execute_contract(Addr::unchecked("taker"), dst.clone(), &ExecuteMsg::CancelDst { escrow_id: 1 }, &[])
+        .unwrap();
+    assert!(query_pending_cancellations(&app, &dst).is_empty());
+}
+
+#[test]
+fn test_keeper_cancels_discovered_source_escrow() {
+    let mut app = mock_app();
+    let start = app.block_info().time;
+    let msg = InstantiateMsg { public_caller_reward: Uint128::new(40), ..test_instantiate_msg(EscrowType::Source) };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let keeper_cancel = ExecuteMsg::KeeperCancel { escrow_id: 1 };
+
+    // Private cancellation is still the parties' alone, and public cancellation
+    // stays with access token holders until the rescue delay has passed
+    for at in [3 * 3600, 4 * 3600] {
+        app.update_block(|block| block.time = start.plus_seconds(at));
+        let err = app
+            .execute_contract(Addr::unchecked("keeper"), contract_addr.clone(), &keeper_cancel, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::TimelockNotExpired {
+                stage: "Rescue delay not expired".to_string(),
+                required_at: start.seconds() + 86400,
+                current: start.seconds() + at,
+            }
+        );
+        assert!(!query_action_preview(&app, &contract_addr, EscrowAction::KeeperCancel).permitted);
+    }
+
+    // After that any keeper, without an access token, cancels it
+    app.update_block(|block| block.time = start.plus_seconds(86400));
+    let pending = query_pending_cancellations(&app, &contract_addr);
+    let reward = pending[0].keeper_reward.clone().unwrap();
+    let response = app
+        .execute_contract(Addr::unchecked("keeper"), contract_addr.clone(), &keeper_cancel, &[])
+        .unwrap();
+    assert_eq!(wasm_attribute(&response, "method"), Some("keeper_cancel".to_string()));
+
+    // The maker is refunded, the keeper earns the advertised reward, the creator the rest of its deposit
+    assert_eq!(app.wrap().query_balance("keeper", "uatom").unwrap(), reward);
+    assert_eq!(app.wrap().query_balance("maker", "uatom").unwrap().amount, Uint128::new(2000 + 1000));
+    assert_eq!(app.wrap().query_balance("owner", "uatom").unwrap().amount, Uint128::new(10000 - 1100 + 60));
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Cancelled);
+    assert!(query_pending_cancellations(&app, &contract_addr).is_empty());
+
+    // Destination escrows are left to their parties and the sweep
+    let dst = instantiate_escrow(&mut app, &test_instantiate_msg(EscrowType::Destination), &[Coin::new(1100, "uatom")]);
+    let err = app
+        .execute_contract(Addr::unchecked("keeper"), dst, &keeper_cancel, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::WrongEscrowType { .. }));
+}
+
+#[test]
+fn test_withdraw_src_recipient_override() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+
+    // The override must be a valid address
+    let err = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr.clone(),
+            &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret: secret.clone(), recipient: Some("INVALID".to_string()) },
+            &[],
+        )
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Std(_)));
+
+    let response = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr,
+            &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: Some("treasury".to_string()) },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(wasm_attribute(&response, "recipient"), Some("treasury".to_string()));
+
+    // Principal goes to the payout address, the deposit still to the caller
+    assert_eq!(app.wrap().query_balance("treasury", "uatom").unwrap().amount, Uint128::new(1000));
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(2100));
+}
+
+#[test]
+fn test_extend_timelocks_defers_cancellation() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg {
+        max_timelock_extension_hours: Some(3),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+    let extend = |additional_hours| ExecuteMsg::ExtendTimelocks { escrow_id: 1, additional_hours };
+
+    let err = app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &extend(2), &[]).unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::OnlyMaker {});
+    app.execute_contract(Addr::unchecked("maker"), contract_addr.clone(), &extend(2), &[]).unwrap();
+
+    // Only the cancellation stages move
+    let timelocks = query_escrow(&app, &contract_addr).unpacked_timelocks;
+    assert_eq!(
+        (timelocks.src_withdrawal, timelocks.src_public_withdrawal, timelocks.src_cancellation, timelocks.src_public_cancellation),
+        (1, 2, 5, 6)
+    );
+
+    // Extensions share the configured cap
+    let err = app.execute_contract(Addr::unchecked("maker"), contract_addr.clone(), &extend(2), &[]).unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidTime { .. }));
+
+    // The original cancellation time has no effect any more
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    let err = app
+        .execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &ExecuteMsg::CancelSrc { escrow_id: 1 }, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::TimelockNotExpired { .. }));
+
+    // Once cancellation opens the refund can't be deferred again
+    app.update_block(|block| block.time = deployed_at.plus_seconds(5 * 3600));
+    let err = app.execute_contract(Addr::unchecked("maker"), contract_addr.clone(), &extend(1), &[]).unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::TimelockExpired { .. }));
+    app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &ExecuteMsg::CancelSrc { escrow_id: 1 }, &[])
+        .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Cancelled);
+}
+
+#[test]
+fn test_timelock_skew_extends_withdrawal_window() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+    let secret = generate_secret();
+    let base = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let withdraw = ExecuteMsg::WithdrawSrc { escrow_id: 1, secret: secret.clone(), recipient: None };
+
+    let too_wide = InstantiateMsg { timelock_skew_seconds: Some(301), ..base.clone() };
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &too_wide, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidTime { .. }));
+
+    // Without skew the window closes exactly when cancellation opens
+    let strict = instantiate_escrow(&mut app, &base, &[Coin::new(1100, "uatom")]);
+    let skewed_msg = InstantiateMsg { timelock_skew_seconds: Some(60), ..base };
+    let skewed = instantiate_escrow(&mut app, &skewed_msg, &[Coin::new(1100, "uatom")]);
+    let skewed_late = instantiate_escrow(&mut app, &skewed_msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    let err = app.execute_contract(Addr::unchecked("taker"), strict, &withdraw, &[]).unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::TimelockExpired { .. }));
+
+    // With skew it stays open for that long past the boundary, and no longer
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600 + 59));
+    app.execute_contract(Addr::unchecked("taker"), skewed.clone(), &withdraw, &[]).unwrap();
+    assert_eq!(query_escrow(&app, &skewed).status, EscrowStatus::Withdrawn);
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600 + 60));
+    let err = app.execute_contract(Addr::unchecked("taker"), skewed_late, &withdraw, &[]).unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::TimelockExpired { .. }));
+}
+
+#[test]
+fn test_reveal_secret_then_withdraw_src() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+
+    // Only the taker reveals, and only the matching secret
+    let err = app
+        .execute_contract(
+            Addr::unchecked("maker"),
+            contract_addr.clone(),
+            &ExecuteMsg::RevealSecret { escrow_id: 1, secret: secret.clone() },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::OnlyTaker {});
+    let err = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr.clone(),
+            &ExecuteMsg::RevealSecret { escrow_id: 1, secret: format!("{secret}x") },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSecret {});
+
+    // Revealing works before the withdrawal window and moves nothing
+    let response = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr.clone(),
+            &ExecuteMsg::RevealSecret { escrow_id: 1, secret: secret.clone() },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(wasm_attribute(&response, "method"), Some("secret_revealed".to_string()));
+    assert_eq!(wasm_attribute(&response, "secret"), Some(secret.clone()));
+    let escrow = query_escrow(&app, &contract_addr);
+    assert_eq!(escrow.status, EscrowStatus::Active);
+    assert_eq!(escrow.revealed_secret, Some(secret.clone()));
+    assert_eq!(escrow.balance, Uint128::new(1000));
+    assert_eq!(app.wrap().query_balance(&contract_addr, "uatom").unwrap().amount, Uint128::new(1100));
+
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Withdrawn);
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(2000 + 1100));
+}
+
+#[test]
+fn test_reveal_secret_blocks_every_cancel_path() {
+    let mut app = mock_app();
+    let (maker_key, maker_pubkey) = test_signing_key(7);
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        maker_pubkey: Some(maker_pubkey.clone()),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::RevealSecret { escrow_id: 1, secret },
+        &[],
+    )
+    .unwrap();
+
+    // Past the rescue delay every refund path would otherwise be open
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(86400));
+    let attempts = [
+        ("taker", ExecuteMsg::CancelSrc { escrow_id: 1 }),
+        ("maker", ExecuteMsg::CancelSrc { escrow_id: 1 }),
+        (
+            "relayer",
+            ExecuteMsg::CancelSrcSigned {
+                escrow_id: 1,
+                maker_sig: sign_cancellation(&maker_key, &contract_addr, &msg.order_hash),
+                maker_pubkey,
+            },
+        ),
+        ("access_token", ExecuteMsg::PublicCancelSrc { escrow_id: 1, proof_sig: None }),
+        (
+            "taker",
+            ExecuteMsg::MutualCancel { escrow_id: 1, counterparty_sig: sign_mutual_cancel(&maker_key, &contract_addr) },
+        ),
+        ("keeper", ExecuteMsg::KeeperCancel { escrow_id: 1 }),
+        ("keeper", ExecuteMsg::SweepExpired { escrow_id: 1 }),
+    ];
+    for (caller, cancel_msg) in attempts {
+        let err = app
+            .execute_contract(Addr::unchecked(caller), contract_addr.clone(), &cancel_msg, &[])
+            .unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::SecretAlreadyRevealed { escrow_id: 1 });
+    }
+    for action in [EscrowAction::CancelSrc, EscrowAction::PublicCancelSrc, EscrowAction::KeeperCancel, EscrowAction::SweepExpired] {
+        assert!(!query_action_preview(&app, &contract_addr, action).permitted);
+    }
+
+    // The principal stays put for the taker to withdraw
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Active);
+    assert_eq!(app.wrap().query_balance(&contract_addr, "uatom").unwrap().amount, Uint128::new(1100));
+}
+
+#[test]
+fn test_withdraw_src_defaults_to_taker() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+
+    let response = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr,
+            &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(wasm_attribute(&response, "recipient"), Some("taker".to_string()));
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(3100));
+}
+
+#[test]
+fn test_swap_id_matches_across_legs() {
+    let mut app = mock_app();
+    let src_response = instantiate_escrow_with_response(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+    let dst_response = instantiate_escrow_with_response(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Destination),
+        &[Coin::new(1100, "uatom")],
+    );
+
+    let expected = hash_secret(&format!("{TEST_ORDER_HASH}{TEST_HASHLOCK}"));
+    assert_eq!(wasm_attribute(&src_response, "swap_id"), Some(expected.clone()));
+    assert_eq!(wasm_attribute(&dst_response, "swap_id"), Some(expected.clone()));
+
+    // A different hashlock is a different swap
+    let other = instantiate_escrow_with_response(
+        &mut app,
+        &InstantiateMsg { hashlock: hash_secret(&generate_secret()), ..test_instantiate_msg(EscrowType::Source) },
+        &[Coin::new(1100, "uatom")],
+    );
+    assert_ne!(wasm_attribute(&other, "swap_id"), Some(expected.clone()));
+
+    let contract_addr = Addr::unchecked(wasm_attribute(&dst_response, "_contract_address").unwrap());
+    let escrow: escrow_contract::msg::EscrowResponse = app
+        .wrap()
+        .query_wasm_smart(&contract_addr, &QueryMsg::EscrowBySwapId { swap_id: expected.clone() })
+        .unwrap();
+    assert_eq!(escrow.swap_id, expected);
+    assert_eq!(escrow.escrow_type, EscrowType::Destination);
+}
+
+#[test]
+fn test_withdrawal_deadline_forces_cancellation() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        withdrawal_deadline_offset: Some(2),
+        ..test_instantiate_msg(EscrowType::Destination)
+    };
+
+    // Just before the deadline the withdrawal goes through
+    let early = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let late = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        early.clone(),
+        &ExecuteMsg::WithdrawDst { escrow_id: 1, secret: secret.clone() },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(query_escrow(&app, &early).status, EscrowStatus::Withdrawn);
+
+    // Past it the public window is still nominally open, but withdrawal is refused
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600 + 1));
+    let err = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            late.clone(),
+            &ExecuteMsg::WithdrawDst { escrow_id: 1, secret },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TimelockExpired { stage: "Withdrawal deadline".to_string() }
+    );
+    assert!(query_available_actions(&app, &late).is_empty());
+}
+
+#[test]
+fn test_withdrawal_deadline_must_follow_withdrawal_stage() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+    let msg = InstantiateMsg {
+        withdrawal_deadline_offset: Some(1),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &msg, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidTime { .. }));
+}
+
+#[test]
+fn test_cancellation_offset_below_minimum_rejected() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+    let msg = InstantiateMsg {
+        timelocks: PackedTimelocks::new(1000, 0, 1, 2, 4, 1, 2, 3),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &msg, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTimelockStage {
+            stage: format!("SrcCancellation at 7200s is earlier than the minimum {MIN_CANCELLATION_OFFSET}s")
+        }
+    );
+
+    // Exactly at the minimum is fine
+    assert_eq!(MIN_CANCELLATION_OFFSET, 3 * 3600);
+    instantiate_escrow(&mut app, &test_instantiate_msg(EscrowType::Source), &[Coin::new(1100, "uatom")]);
+}
+
+fn sign_authority_proof(signing_key: &SigningKey, contract_addr: &Addr, caller: &str) -> Binary {
+    let message_hash = public_authority_proof_hash(contract_addr, 1, &Addr::unchecked(caller));
+    let signature: Signature = signing_key.sign_prehash(&message_hash).unwrap();
+    Binary::from(signature.to_bytes().to_vec())
+}
+
+#[test]
+fn test_public_cancel_with_authority_proof() {
+    let mut app = mock_app();
+    let (authority_key, authority_pubkey) = test_signing_key(21);
+    let (other_key, _) = test_signing_key(23);
+    let msg = InstantiateMsg {
+        public_authority_pubkey: Some(authority_pubkey),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+
+    let cancel = |proof_sig| ExecuteMsg::PublicCancelSrc { escrow_id: 1, proof_sig };
+
+    // Without a proof the caller still needs the access token
+    let err = app
+        .execute_contract(Addr::unchecked("keeper"), contract_addr.clone(), &cancel(None), &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::OnlyAccessTokenHolder {});
+
+    // Proofs from another key, or for another caller, are rejected
+    let forged = sign_authority_proof(&other_key, &contract_addr, "keeper");
+    let err = app
+        .execute_contract(Addr::unchecked("keeper"), contract_addr.clone(), &cancel(Some(forged)), &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSignature {});
+    let for_other = sign_authority_proof(&authority_key, &contract_addr, "other_keeper");
+    let err = app
+        .execute_contract(Addr::unchecked("keeper"), contract_addr.clone(), &cancel(Some(for_other)), &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSignature {});
+
+    let proof = sign_authority_proof(&authority_key, &contract_addr, "keeper");
+    app.execute_contract(Addr::unchecked("keeper"), contract_addr.clone(), &cancel(Some(proof)), &[])
+        .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Cancelled);
+    assert_eq!(app.wrap().query_balance("keeper", "uatom").unwrap().amount, Uint128::new(100));
+}
+
+#[test]
+fn test_public_withdraw_dst_with_authority_proof() {
+    let mut app = mock_app();
+    let (authority_key, authority_pubkey) = test_signing_key(21);
+    let msg = InstantiateMsg {
+        public_authority_pubkey: Some(authority_pubkey),
+        ..test_instantiate_msg(EscrowType::Destination)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600));
+
+    let proof = sign_authority_proof(&authority_key, &contract_addr, "keeper");
+    app.execute_contract(
+        Addr::unchecked("keeper"),
+        contract_addr.clone(),
+        &ExecuteMsg::PublicWithdrawDst { escrow_id: 1, proof_sig: Some(proof), secret: None },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(app.wrap().query_balance("maker", "uatom").unwrap().amount, Uint128::new(3000));
+}
+
+#[test]
+fn test_escrows_by_party() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+    let by_maker = |app: &App, maker: &str, start_after| -> EscrowsResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::EscrowsByMaker { maker: maker.to_string(), start_after, limit: None },
+            )
+            .unwrap()
+    };
+    let by_taker = |app: &App, taker: &str| -> EscrowsResponse {
+        app.wrap()
+            .query_wasm_smart(
+                &contract_addr,
+                &QueryMsg::EscrowsByTaker { taker: taker.to_string(), start_after: None, limit: None },
+            )
+            .unwrap()
+    };
+
+    let escrows = by_maker(&app, "maker", None).escrows;
+    assert_eq!(escrows.len(), 1);
+    assert_eq!(escrows[0].escrow_id, 1);
+    assert_eq!(escrows[0].immutables.maker, Addr::unchecked("maker"));
+    assert!(by_maker(&app, "maker", Some(1)).escrows.is_empty());
+    assert!(by_maker(&app, "taker", None).escrows.is_empty());
+
+    // Finalized escrows stay in the party's history
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &ExecuteMsg::CancelSrc { escrow_id: 1 }, &[])
+        .unwrap();
+    let escrows = by_taker(&app, "taker").escrows;
+    assert_eq!(escrows.len(), 1);
+    assert_eq!(escrows[0].status, EscrowStatus::Cancelled);
+    assert!(by_taker(&app, "maker").escrows.is_empty());
+}
+
+fn load_escrow_state(app: &App, contract_addr: &Addr) -> EscrowState {
+    let raw = app.wrap().query_wasm_raw(contract_addr, ESCROWS.key(1).to_vec()).unwrap().unwrap();
+    cosmwasm_std::from_json(raw).unwrap()
+}
+
+#[test]
+fn test_balance_debits_error_on_underflow() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+    let mut escrow_state = load_escrow_state(&app, &contract_addr);
+
+    assert_eq!(
+        escrow_state.debit_principal(Uint128::new(1001)),
+        Err(ContractError::InsufficientBalance { required: "1001".to_string(), available: "1000".to_string() })
+    );
+    assert_eq!(
+        escrow_state.debit_deposit(Uint128::new(101)),
+        Err(ContractError::InsufficientBalance { required: "101".to_string(), available: "100".to_string() })
+    );
+    assert_eq!(escrow_state.balance, Uint128::new(1000));
+
+    escrow_state.debit_principal(Uint128::new(400)).unwrap();
+    escrow_state.debit_deposit(Uint128::new(100)).unwrap();
+    assert_eq!(escrow_state.balance, Uint128::new(600));
+    assert_eq!(escrow_state.native_balance, Uint128::zero());
+}
+
+#[test]
+fn test_public_cancel_debits_the_deposit() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg {
+        public_caller_reward: Uint128::new(30),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+    app.execute_contract(
+        Addr::unchecked("access_token"),
+        contract_addr.clone(),
+        &ExecuteMsg::PublicCancelSrc { escrow_id: 1, proof_sig: None },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(app.wrap().query_balance("access_token", "uatom").unwrap().amount, Uint128::new(30));
+    assert_eq!(load_escrow_state(&app, &contract_addr).native_balance, Uint128::zero());
+}
+
+fn query_withdraw_fees_paid(app: &App, contract_addr: &Addr) -> Uint128 {
+    let raw = app.wrap().query_wasm_raw(contract_addr, WITHDRAW_FEES_PAID.as_slice().to_vec()).unwrap().unwrap();
+    cosmwasm_std::from_json(raw).unwrap()
+}
+
+#[test]
+fn test_withdraw_fee_sent_to_treasury() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        withdraw_fee: Uint128::new(30),
+        treasury: Some("protocol_treasury".to_string()),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+
+    let response = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr.clone(),
+            &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(wasm_attribute(&response, "withdraw_fee"), Some("30".to_string()));
+
+    // Principal and the remaining 70 of the deposit go to the taker
+    assert_eq!(app.wrap().query_balance("protocol_treasury", "uatom").unwrap().amount, Uint128::new(30));
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(3070));
+    assert_eq!(query_withdraw_fees_paid(&app, &contract_addr), Uint128::new(30));
+}
+
+#[test]
+fn test_withdraw_fee_capped_at_safety_deposit() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        withdraw_fee: Uint128::new(500),
+        treasury: Some("protocol_treasury".to_string()),
+        ..test_instantiate_msg(EscrowType::Destination)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawDst { escrow_id: 1, secret },
+        &[],
+    )
+    .unwrap();
+
+    // The whole deposit is taken, the principal is untouched
+    assert_eq!(app.wrap().query_balance("protocol_treasury", "uatom").unwrap().amount, Uint128::new(100));
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(2000));
+    assert_eq!(app.wrap().query_balance("maker", "uatom").unwrap().amount, Uint128::new(3000));
+    assert_eq!(query_withdraw_fees_paid(&app, &contract_addr), Uint128::new(100));
+}
+
+#[test]
+fn test_withdraw_fee_charged_on_public_withdrawal() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg {
+        withdraw_fee: Uint128::new(30),
+        treasury: Some("protocol_treasury".to_string()),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let src = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let dst = instantiate_escrow(&mut app, &InstantiateMsg { escrow_type: EscrowType::Destination, ..msg }, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600));
+
+    let preview = query_action_preview(&app, &src, EscrowAction::PublicWithdrawSrc);
+    assert_eq!(preview.deposit_amount, Uint128::new(70));
+    let response = app
+        .execute_contract(
+            Addr::unchecked("access_token"),
+            src.clone(),
+            &ExecuteMsg::PublicWithdrawSrc { escrow_id: 1, proof_sig: None, secret: None },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(wasm_attribute(&response, "withdraw_fee"), Some("30".to_string()));
+    let response = app
+        .execute_contract(
+            Addr::unchecked("access_token"),
+            dst.clone(),
+            &ExecuteMsg::PublicWithdrawDst { escrow_id: 1, proof_sig: None, secret: None },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(wasm_attribute(&response, "withdraw_fee"), Some("30".to_string()));
+
+    // Each public caller keeps the deposit less the treasury's fee
+    assert_eq!(app.wrap().query_balance("protocol_treasury", "uatom").unwrap().amount, Uint128::new(60));
+    assert_eq!(app.wrap().query_balance("access_token", "uatom").unwrap().amount, Uint128::new(140));
+    assert_eq!(query_withdraw_fees_paid(&app, &src), Uint128::new(30));
+    assert_eq!(query_withdraw_fees_paid(&app, &dst), Uint128::new(30));
+}
+
+#[test]
+fn test_action_preview_excludes_withdraw_fee() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg { withdraw_fee: Uint128::new(30), ..test_instantiate_msg(EscrowType::Source) };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let preview: ActionPreviewResponse = app
+        .wrap()
+        .query_wasm_smart(&contract_addr, &QueryMsg::ActionPreview { escrow_id: 1, action: EscrowAction::WithdrawSrc })
+        .unwrap();
+    assert_eq!(preview.deposit_amount, Uint128::new(70));
+}
+
+fn query_total_value_locked(app: &App, contract_addr: &Addr) -> Vec<AssetTotal> {
+    let response: TotalValueLockedResponse = app
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::TotalValueLocked {})
+        .unwrap();
+    response.assets
+}
+
+#[test]
+fn test_total_value_locked_native_escrow() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    // Principal and deposit share a denom, so they add up to one entry
+    assert_eq!(
+        query_total_value_locked(&app, &contract_addr),
+        vec![AssetTotal { asset: AssetKind::Native("uatom".to_string()), total: Uint128::new(1100) }]
+    );
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+
+    assert!(query_total_value_locked(&app, &contract_addr).is_empty());
+}
+
+#[test]
+fn test_total_value_locked_cw20_escrow() {
+    let mut app = mock_app();
+    let token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin { address: "owner".to_string(), amount: Uint128::new(5000) }],
+    );
+    let msg = InstantiateMsg {
+        token: token.to_string(),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(100, "uatom")]);
+
+    // The principal only counts once the receive hook delivers it
+    assert_eq!(
+        query_total_value_locked(&app, &contract_addr),
+        vec![AssetTotal { asset: AssetKind::Native("uatom".to_string()), total: Uint128::new(100) }]
+    );
+    fund_cw20_escrow(&mut app, &token, &contract_addr, "owner", 1000);
+    assert_eq!(
+        query_total_value_locked(&app, &contract_addr),
+        vec![
+            AssetTotal { asset: AssetKind::Cw20(token.clone()), total: Uint128::new(1000) },
+            AssetTotal { asset: AssetKind::Native("uatom".to_string()), total: Uint128::new(100) },
+        ]
+    );
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::CancelSrc { escrow_id: 1 },
+        &[],
+    )
+    .unwrap();
+
+    assert!(query_total_value_locked(&app, &contract_addr).is_empty());
+}
+
+#[test]
+fn test_create_escrow_refuses_existing_id() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+    let escrow_state = load_escrow_state(&app, &contract_addr);
+
+    let mut storage = cosmwasm_std::testing::MockStorage::new();
+    create_escrow(&mut storage, 1, &escrow_state).unwrap();
+
+    // A second escrow under the same id must not replace the first
+    let mut other = escrow_state.clone();
+    other.balance = Uint128::new(1);
+    assert_eq!(
+        create_escrow(&mut storage, 1, &other),
+        Err(ContractError::EscrowAlreadyExists { hash: escrow_state.escrow_info.swap_id.clone() })
+    );
+    assert_eq!(ESCROWS.load(&storage, 1).unwrap(), escrow_state);
+
+    create_escrow(&mut storage, 2, &other).unwrap();
+}
+
+fn query_invariants(app: &App, contract_addr: &Addr) -> Vec<InvariantViolation> {
+    let response: InvariantsResponse = app
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::Invariants { start_after: None, limit: None })
+        .unwrap();
+    response.escrows
+}
+
+#[test]
+fn test_invariants_hold_through_lifecycle() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    assert!(query_invariants(&app, &contract_addr).is_empty());
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+    assert!(query_invariants(&app, &contract_addr).is_empty());
+}
+
+type MockDeps = cosmwasm_std::OwnedDeps<cosmwasm_std::MemoryStorage, cosmwasm_std::testing::MockApi, cosmwasm_std::testing::MockQuerier>;
+
+/// Instantiate an escrow directly against mock storage so tests can corrupt it
+fn mock_escrow_deps() -> MockDeps {
+    let mut deps = cosmwasm_std::testing::mock_dependencies();
+    escrow_contract::instantiate(
+        deps.as_mut(),
+        cosmwasm_std::testing::mock_env(),
+        cosmwasm_std::testing::mock_info("owner", &[Coin::new(1100, "uatom")]),
+        test_instantiate_msg(EscrowType::Source),
+    )
+    .unwrap();
+    deps
+}
+
+fn mock_invariants(deps: &MockDeps) -> Vec<InvariantViolation> {
+    let msg = QueryMsg::Invariants { start_after: None, limit: None };
+    let raw = escrow_contract::query(deps.as_ref(), cosmwasm_std::testing::mock_env(), msg).unwrap();
+    let response: InvariantsResponse = cosmwasm_std::from_json(raw).unwrap();
+    response.escrows
+}
+
+fn query_metrics(app: &App, contract_addr: &Addr) -> MetricsResponse {
+    app.wrap().query_wasm_smart(contract_addr, &QueryMsg::Metrics {}).unwrap()
+}
+
+fn mock_metrics(deps: &MockDeps) -> MetricsResponse {
+    let raw = escrow_contract::query(deps.as_ref(), cosmwasm_std::testing::mock_env(), QueryMsg::Metrics {}).unwrap();
+    cosmwasm_std::from_json(raw).unwrap()
+}
+
+#[test]
+fn test_metrics_track_top_up_and_withdrawal() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    app.execute_contract(
+        Addr::unchecked("maker"),
+        contract_addr.clone(),
+        &ExecuteMsg::TopUp { escrow_id: 1 },
+        &[Coin::new(500, "uatom")],
+    )
+    .unwrap();
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_metrics(&app, &contract_addr),
+        MetricsResponse {
+            created: 1,
+            withdrawn: 1,
+            cancelled: 0,
+            rescued: 0,
+            admin_rescued: 0,
+            principal_volume: vec![AssetTotal { asset: AssetKind::Native("uatom".to_string()), total: Uint128::new(1500) }],
+        }
+    );
+}
+
+#[test]
+fn test_metrics_sum_across_lifecycles() {
+    let mut deps = mock_escrow_deps();
+    let template = ESCROWS.load(&deps.storage, 1).unwrap();
+    let cw20 = AssetKind::Cw20(Addr::unchecked("token"));
+
+    // Escrow 1 stays active, the rest each end up in a different status
+    let finals = [EscrowStatus::Withdrawn, EscrowStatus::Cancelled, EscrowStatus::Rescued, EscrowStatus::AdminRescued];
+    for (index, status) in finals.into_iter().enumerate() {
+        let escrow_id = index as u64 + 2;
+        let mut escrow_state = template.clone();
+        if status == EscrowStatus::Cancelled {
+            escrow_state.escrow_info.immutables.token = cw20.clone();
+        }
+        create_escrow(&mut deps.storage, escrow_id, &escrow_state).unwrap();
+        lock_value(&mut deps.storage, &escrow_state).unwrap();
+
+        escrow_state.escrow_info.status = status;
+        ESCROWS.save(&mut deps.storage, escrow_id, &escrow_state).unwrap();
+        release_value(&mut deps.storage, &escrow_state).unwrap();
+    }
+
+    let metrics = mock_metrics(&deps);
+    assert_eq!(metrics.created, 5);
+    assert_eq!(metrics.created - (metrics.withdrawn + metrics.cancelled + metrics.rescued + metrics.admin_rescued), 1);
+    assert_eq!((metrics.withdrawn, metrics.cancelled, metrics.rescued, metrics.admin_rescued), (1, 1, 1, 1));
+    assert_eq!(
+        metrics.principal_volume,
+        vec![
+            AssetTotal { asset: AssetKind::Native("uatom".to_string()), total: Uint128::new(4000) },
+            AssetTotal { asset: cw20, total: Uint128::new(1000) },
+        ]
+    );
+}
+
+#[test]
+fn test_invariants_report_corrupted_escrow() {
+    let mut deps = mock_escrow_deps();
+    assert!(mock_invariants(&deps).is_empty());
+
+    // Active but emptied, marked completed, and dropped from the maker index
+    let mut escrow_state = ESCROWS.load(&deps.storage, 1).unwrap();
+    escrow_state.balance = Uint128::zero();
+    escrow_state.native_balance = Uint128::zero();
+    escrow_state.escrow_info.completed_at_height = Some(escrow_state.escrow_info.created_at_height);
+    ESCROWS.save(&mut deps.storage, 1, &escrow_state).unwrap();
+    MAKER_ESCROWS.remove(&mut deps.storage, (&escrow_state.escrow_info.immutables.maker, 1));
+
+    assert_eq!(
+        mock_invariants(&deps),
+        vec![InvariantViolation {
+            escrow_id: 1,
+            violations: vec![
+                "active escrow holds neither principal nor safety deposit".to_string(),
+                "active escrow has a completion height".to_string(),
+                "missing from maker index".to_string(),
+            ],
+        }]
+    );
+}
+
+#[test]
+fn test_invariants_report_finalized_escrow_without_completion_height() {
+    let mut deps = mock_escrow_deps();
+    let mut escrow_state = ESCROWS.load(&deps.storage, 1).unwrap();
+    escrow_state.escrow_info.status = EscrowStatus::Cancelled;
+    ESCROWS.save(&mut deps.storage, 1, &escrow_state).unwrap();
+
+    assert_eq!(
+        mock_invariants(&deps),
+        vec![InvariantViolation { escrow_id: 1, violations: vec!["Cancelled escrow has no completion height".to_string()] }]
+    );
+}
+
+fn sign_order(signing_key: &SigningKey, msg: &InstantiateMsg) -> Binary {
+    let message_hash = order_commitment_hash(&msg.order_hash, &msg.hashlock, msg.amount, &msg.token);
+    let signature: Signature = signing_key.sign_prehash(&message_hash).unwrap();
+    Binary::from(signature.to_bytes().to_vec())
+}
+
+#[test]
+fn test_signed_order_creation() {
+    let mut app = mock_app();
+    let (maker_key, maker_pubkey) = test_signing_key(17);
+    let msg = InstantiateMsg {
+        maker_pubkey: Some(maker_pubkey),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let msg = InstantiateMsg { order_sig: Some(sign_order(&maker_key, &msg)), ..msg };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    assert_eq!(query_escrow(&app, &contract_addr).immutables.hashlock, msg.hashlock);
+}
+
+#[test]
+fn test_signed_order_rejects_other_hashlock() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+    let (maker_key, maker_pubkey) = test_signing_key(17);
+    let signed = InstantiateMsg {
+        maker_pubkey: Some(maker_pubkey),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let order_sig = sign_order(&maker_key, &signed);
+
+    // The taker swaps in a hashlock of its own choosing
+    let tampered = InstantiateMsg {
+        hashlock: hash_secret(&generate_secret()),
+        order_sig: Some(order_sig.clone()),
+        ..signed.clone()
+    };
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &tampered, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSignature {});
+
+    // Without a maker key there is nothing to check the signature against
+    let keyless = InstantiateMsg { maker_pubkey: None, order_sig: Some(order_sig), ..signed };
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &keyless, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSignature {});
+}
+
+#[test]
+fn test_immutables_hash_queries() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+    let immutables = query_escrow(&app, &contract_addr).immutables;
+
+    // Field order of Immutables::hash, recomputed by hand as a relayer would
+    let mut hasher = Sha256::new();
+    hasher.update(TEST_ORDER_HASH.as_bytes());
+    hasher.update(TEST_HASHLOCK.as_bytes());
+    hasher.update(b"maker");
+    hasher.update(b"taker");
+    hasher.update(b"uatom");
+    hasher.update(b"1000");
+    hasher.update(b"100");
+    hasher.update(immutables.timelocks.source_data.to_string().as_bytes());
+    hasher.update(immutables.timelocks.destination_data.to_string().as_bytes());
+    let expected = format!("{:x}", hasher.finalize());
+
+    let stored: ImmutablesHashResponse = app
+        .wrap()
+        .query_wasm_smart(&contract_addr, &QueryMsg::ImmutablesHash { escrow_id: 1 })
+        .unwrap();
+    assert_eq!(stored.hash, expected);
+
+    let computed: ImmutablesHashResponse = app
+        .wrap()
+        .query_wasm_smart(&contract_addr, &QueryMsg::ComputeImmutablesHash { params: immutables.clone() })
+        .unwrap();
+    assert_eq!(computed.hash, expected);
+
+    // Any difference from the stored escrow shows up in the hash
+    let other: ImmutablesHashResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &contract_addr,
+            &QueryMsg::ComputeImmutablesHash { params: Immutables { amount: Uint128::new(999), ..immutables } },
+        )
+        .unwrap();
+    assert_ne!(other.hash, expected);
+}
+
+#[test]
+fn test_top_up_native_escrow() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    // Only the maker funded a source escrow's principal
+    let err = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr.clone(),
+            &ExecuteMsg::TopUp { escrow_id: 1 },
+            &[Coin::new(500, "uatom")],
+        )
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized { .. }));
+
+    // The principal denom only
+    let err = app
+        .execute_contract(
+            Addr::unchecked("owner"),
+            contract_addr.clone(),
+            &ExecuteMsg::TopUp { escrow_id: 1 },
+            &[Coin::new(500, "uosmo")],
+        )
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidTokenAddress { .. }));
+
+    let response = app
+        .execute_contract(
+            Addr::unchecked("maker"),
+            contract_addr.clone(),
+            &ExecuteMsg::TopUp { escrow_id: 1 },
+            &[Coin::new(500, "uatom")],
+        )
+        .unwrap();
+    assert_eq!(wasm_attribute(&response, "balance"), Some("1500".to_string()));
+    let escrow = query_escrow(&app, &contract_addr);
+    assert_eq!(escrow.balance, Uint128::new(1500));
+    assert_eq!(escrow.immutables.amount, Uint128::new(1000));
+    assert_eq!(escrow.immutables.hashlock, msg.hashlock);
+    assert_eq!(
+        query_total_value_locked(&app, &contract_addr),
+        vec![AssetTotal { asset: AssetKind::Native("uatom".to_string()), total: Uint128::new(1600) }]
+    );
+
+    // The taker's withdrawal releases the topped-up principal
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(2000 + 1500 + 100));
+    assert!(query_total_value_locked(&app, &contract_addr).is_empty());
+}
+
+#[test]
+fn test_top_up_rejected_once_cancellation_opens() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    let err = app
+        .execute_contract(
+            Addr::unchecked("maker"),
+            contract_addr,
+            &ExecuteMsg::TopUp { escrow_id: 1 },
+            &[Coin::new(500, "uatom")],
+        )
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::TimelockExpired { .. }));
+}
+
+#[test]
+fn test_top_up_keeps_minimum_safety_deposit() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg { safety_deposit: Uint128::new(10), ..test_instantiate_msg(EscrowType::Source) };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1010, "uatom")]);
+
+    // A 10 deposit falls short of 1% of 1100
+    let err = app
+        .execute_contract(
+            Addr::unchecked("maker"),
+            contract_addr.clone(),
+            &ExecuteMsg::TopUp { escrow_id: 1 },
+            &[Coin::new(100, "uatom")],
+        )
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidAmount { .. }));
+    assert_eq!(query_escrow(&app, &contract_addr).balance, Uint128::new(1000));
+}
+
+#[test]
+fn test_top_up_cw20_escrow() {
+    let mut app = mock_app();
+    let token = instantiate_cw20(
+        &mut app,
+        vec![
+            Cw20Coin { address: "owner".to_string(), amount: Uint128::new(1000) },
+            Cw20Coin { address: "taker".to_string(), amount: Uint128::new(1000) },
+        ],
+    );
+    // The taker funds a destination escrow's principal
+    let msg = InstantiateMsg {
+        token: token.to_string(),
+        ..test_instantiate_msg(EscrowType::Destination)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(100, "uatom")]);
+    let top_up = Cw20ExecuteMsg::Send {
+        contract: contract_addr.to_string(),
+        amount: Uint128::new(250),
+        msg: to_json_binary(&Cw20HookMsg::TopUp { escrow_id: 1 }).unwrap(),
+    };
+
+    // Nothing can be added before the principal itself arrives
+    let err = app
+        .execute_contract(Addr::unchecked("taker"), token.clone(), &top_up, &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::PrincipalNotFunded { escrow_id: 1 });
+    fund_cw20_escrow(&mut app, &token, &contract_addr, "owner", 1000);
+    app.execute_contract(Addr::unchecked("taker"), token.clone(), &top_up, &[])
+        .unwrap();
+
+    assert_eq!(query_escrow(&app, &contract_addr).balance, Uint128::new(1250));
+    assert_eq!(query_cw20_balance(&app, &token, contract_addr.as_str()), Uint128::new(1250));
+    assert_eq!(
+        query_total_value_locked(&app, &contract_addr),
+        vec![
+            AssetTotal { asset: AssetKind::Cw20(token), total: Uint128::new(1250) },
+            AssetTotal { asset: AssetKind::Native("uatom".to_string()), total: Uint128::new(100) },
+        ]
+    );
+}
+
+#[test]
+fn test_cw20_send_to_native_escrow_reverts() {
+    let mut app = mock_app();
+    let token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin { address: "owner".to_string(), amount: Uint128::new(5000) }],
+    );
+    let contract_addr =
+        instantiate_escrow(&mut app, &test_instantiate_msg(EscrowType::Source), &[Coin::new(1100, "uatom")]);
+
+    // Neither hook credits CW20 to a native escrow, so the transfer reverts
+    for hook in [Cw20HookMsg::DeployEscrow { escrow_id: 1 }, Cw20HookMsg::TopUp { escrow_id: 1 }] {
+        let err = app
+            .execute_contract(
+                Addr::unchecked("owner"),
+                token.clone(),
+                &Cw20ExecuteMsg::Send {
+                    contract: contract_addr.to_string(),
+                    amount: Uint128::new(1000),
+                    msg: to_json_binary(&hook).unwrap(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidTokenAddress {
+                address: format!("{token} sent to escrow 1 holding native uatom"),
+            }
+        );
+    }
+    assert_eq!(query_cw20_balance(&app, &token, "owner"), Uint128::new(5000));
+    assert_eq!(query_cw20_balance(&app, &token, contract_addr.as_str()), Uint128::zero());
+}
+
+fn query_can_withdraw(app: &App, contract_addr: &Addr, secret: &str) -> CanWithdrawResponse {
+    app.wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::CanWithdraw { escrow_id: 1, secret: secret.to_string() })
+        .unwrap()
+}
+
+#[test]
+fn test_can_withdraw_query() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    // Valid secret, but the withdrawal stage hasn't started
+    assert_eq!(
+        query_can_withdraw(&app, &contract_addr, &secret),
+        CanWithdrawResponse { secret_valid: true, timelock_open: false, authorized_caller: None }
+    );
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+
+    // Wrong secret inside the window
+    assert_eq!(
+        query_can_withdraw(&app, &contract_addr, &generate_secret().replace("secret", "wrong_")),
+        CanWithdrawResponse { secret_valid: false, timelock_open: true, authorized_caller: None }
+    );
+
+    // Valid and open
+    assert_eq!(
+        query_can_withdraw(&app, &contract_addr, &secret),
+        CanWithdrawResponse { secret_valid: true, timelock_open: true, authorized_caller: Some("taker".to_string()) }
+    );
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret: secret.clone(), recipient: None },
+        &[],
+    )
+    .unwrap();
+
+    // Nothing left to withdraw
+    assert!(!query_can_withdraw(&app, &contract_addr, &secret).timelock_open);
+}
+
+#[test]
+fn test_cancel_refunds_deposit_in_its_own_denom() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg {
+        safety_deposit_denom: Some("uosmo".to_string()),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &msg,
+        &[Coin::new(1000, "uatom"), Coin::new(100, "uosmo")],
+    );
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::CancelSrc { escrow_id: 1 },
+        &[],
+    )
+    .unwrap();
+
+    // Principal back to the maker in uatom, deposit to the creator in uosmo
+    assert_eq!(app.wrap().query_balance("maker", "uatom").unwrap().amount, Uint128::new(3000));
+    assert_eq!(app.wrap().query_balance("owner", "uosmo").unwrap().amount, Uint128::new(10000));
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(2000));
+    assert!(app.wrap().query_all_balances(&contract_addr).unwrap().is_empty());
+}
+
+#[test]
+fn test_deposit_recipient_private_vs_public_cancel() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+    let preview = query_action_preview(&app, &contract_addr, EscrowAction::CancelSrc);
+    assert_eq!(preview.deposit_recipient, Some(Addr::unchecked("owner")));
+    let preview = query_action_preview(&app, &contract_addr, EscrowAction::PublicCancelSrc);
+    assert_eq!(preview.deposit_recipient, None);
+
+    // A public cancellation rewards whoever submits it
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+    app.execute_contract(
+        Addr::unchecked("access_token"),
+        contract_addr,
+        &ExecuteMsg::PublicCancelSrc { escrow_id: 1, proof_sig: None },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(app.wrap().query_balance("access_token", "uatom").unwrap().amount, Uint128::new(100));
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(2000));
+    assert_eq!(app.wrap().query_balance("maker", "uatom").unwrap().amount, Uint128::new(3000));
+}
+
+#[test]
+fn test_maker_unsticks_abandoned_destination_escrow() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Destination),
+        &[Coin::new(1100, "uatom")],
+    );
+    let deployed_at = app.block_info().time;
+
+    // Still the taker's swap while withdrawal is open
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600));
+    let err = app
+        .execute_contract(Addr::unchecked("maker"), contract_addr.clone(), &ExecuteMsg::CancelDst { escrow_id: 1 }, &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::TimelockNotExpired { .. }));
+
+    // Strangers never can
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    let err = app
+        .execute_contract(Addr::unchecked("stranger"), contract_addr.clone(), &ExecuteMsg::CancelDst { escrow_id: 1 }, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {
+            reason: "Only the taker, maker or creator can cancel a destination escrow".to_string()
+        }
+    );
+
+    // The taker has gone silent; the maker cancels and everything returns to its funders
+    app.execute_contract(Addr::unchecked("maker"), contract_addr.clone(), &ExecuteMsg::CancelDst { escrow_id: 1 }, &[])
+        .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Cancelled);
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(2000 + 1000));
+    assert_eq!(app.wrap().query_balance("owner", "uatom").unwrap().amount, Uint128::new(10000 - 1100 + 100));
+    assert_eq!(app.wrap().query_balance("maker", "uatom").unwrap().amount, Uint128::new(2000));
+}
+
+#[test]
+fn test_funder_cancels_abandoned_destination_escrow() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Destination),
+        &[Coin::new(1100, "uatom")],
+    );
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+
+    // "owner" instantiated and funded the escrow on the taker's behalf
+    app.execute_contract(Addr::unchecked("owner"), contract_addr.clone(), &ExecuteMsg::CancelDst { escrow_id: 1 }, &[])
+        .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Cancelled);
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(2000 + 1000));
+    assert_eq!(app.wrap().query_balance("owner", "uatom").unwrap().amount, Uint128::new(10000 - 1100 + 100));
+}
+
+#[test]
+fn test_access_token_threshold_per_escrow() {
+    let mut app = mock_app();
+    let access_token = instantiate_cw20(
+        &mut app,
+        vec![
+            Cw20Coin { address: "small_holder".to_string(), amount: Uint128::new(50) },
+            Cw20Coin { address: "large_holder".to_string(), amount: Uint128::new(500) },
+        ],
+    );
+    let msg = InstantiateMsg {
+        access_token: UncheckedAccessToken::Cw20(access_token.to_string()),
+        default_access_token_threshold: Uint128::new(1000),
+        access_token_threshold: Some(Uint128::new(100)),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+
+    let cancel_msg = ExecuteMsg::PublicCancelSrc { escrow_id: 1, proof_sig: None };
+    let err = app
+        .execute_contract(Addr::unchecked("small_holder"), contract_addr.clone(), &cancel_msg, &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::OnlyAccessTokenHolder {});
+
+    app.execute_contract(Addr::unchecked("large_holder"), contract_addr.clone(), &cancel_msg, &[])
+        .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Cancelled);
+    assert_eq!(app.wrap().query_balance("large_holder", "uatom").unwrap().amount, Uint128::new(100));
+}
+
+#[test]
+fn test_access_token_threshold_falls_back_to_config() {
+    let mut app = mock_app();
+    let access_token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin { address: "large_holder".to_string(), amount: Uint128::new(500) }],
+    );
+    let msg = InstantiateMsg {
+        access_token: UncheckedAccessToken::Cw20(access_token.to_string()),
+        default_access_token_threshold: Uint128::new(1000),
+        ..test_instantiate_msg(EscrowType::Destination)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(2 * 3600));
+
+    // 500 falls short of the config-wide 1000
+    let err = app
+        .execute_contract(
+            Addr::unchecked("large_holder"),
+            contract_addr,
+            &ExecuteMsg::PublicWithdrawDst { escrow_id: 1, proof_sig: None, secret: None },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::OnlyAccessTokenHolder {});
+}
+
+#[test]
+fn test_access_token_gated_by_native_balance() {
+    let mut app = mock_app();
+    app.send_tokens(Addr::unchecked("owner"), Addr::unchecked("small_holder"), &[Coin::new(50, "uosmo")])
+        .unwrap();
+    app.send_tokens(Addr::unchecked("owner"), Addr::unchecked("large_holder"), &[Coin::new(500, "uosmo")])
+        .unwrap();
+    let msg = InstantiateMsg {
+        access_token: UncheckedAccessToken::Native("uosmo".to_string()),
+        default_access_token_threshold: Uint128::new(100),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let config: ConfigResponse = app.wrap().query_wasm_smart(&contract_addr, &QueryMsg::Config {}).unwrap();
+    assert_eq!(config.access_token, AccessToken::Native("uosmo".to_string()));
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(4 * 3600));
+
+    // The old access token address is no longer special, only the bank balance counts
+    let cancel_msg = ExecuteMsg::PublicCancelSrc { escrow_id: 1, proof_sig: None };
+    for caller in ["access_token", "small_holder"] {
+        let err = app
+            .execute_contract(Addr::unchecked(caller), contract_addr.clone(), &cancel_msg, &[])
+            .unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::OnlyAccessTokenHolder {});
+    }
+
+    app.execute_contract(Addr::unchecked("large_holder"), contract_addr.clone(), &cancel_msg, &[])
+        .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Cancelled);
+    assert_eq!(app.wrap().query_balance("large_holder", "uatom").unwrap().amount, Uint128::new(100));
+}
+
+#[test]
+fn test_native_access_token_denom_validated() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+    let params = InstantiateMsg {
+        access_token: UncheckedAccessToken::Native("!bad".to_string()),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let response: ValidateParamsResponse = app
+        .wrap()
+        .query_wasm_smart(&contract_addr, &QueryMsg::ValidateParams { params: Box::new(params) })
+        .unwrap();
+    assert!(!response.valid);
+    assert_eq!(response.errors, vec!["Invalid denom: !bad".to_string()]);
+}
+
+fn query_near_rescue(app: &App, contract_addr: &Addr, within_seconds: u64) -> Vec<NearRescueEscrow> {
+    let response: NearRescueResponse = app
+        .wrap()
+        .query_wasm_smart(contract_addr, &QueryMsg::NearRescue { within_seconds, start_after: None, limit: None })
+        .unwrap();
+    response.escrows
+}
+
+#[test]
+fn test_near_rescue_query() {
+    let mut app = mock_app();
+    let msg = test_instantiate_msg(EscrowType::Source);
+    let older = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let older_deployed_at = app.block_info().time.seconds();
+    app.update_block(|block| block.time = block.time.plus_seconds(2 * 3600));
+    let newer = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    // Nothing is near rescue right after creation
+    assert!(query_near_rescue(&app, &older, 3600).is_empty());
+
+    // An hour before the older escrow's rescue, the newer one is still three hours out
+    app.update_block(|block| block.time = block.time.plus_seconds(86400 - 3 * 3600));
+    assert_eq!(
+        query_near_rescue(&app, &older, 3600),
+        vec![NearRescueEscrow { escrow_id: 1, rescue_start: older_deployed_at + 86400, seconds_remaining: 3600 }]
+    );
+    assert!(query_near_rescue(&app, &newer, 3600).is_empty());
+
+    // Already rescuable escrows report zero remaining, until rescued
+    app.update_block(|block| block.time = block.time.plus_seconds(3600));
+    assert_eq!(query_near_rescue(&app, &older, 0)[0].seconds_remaining, 0);
+    assert_eq!(query_near_rescue(&app, &newer, 2 * 3600)[0].seconds_remaining, 2 * 3600);
+    app.execute_contract(Addr::unchecked("taker"), older.clone(), &ExecuteMsg::Rescue { escrow_id: 1 }, &[])
+        .unwrap();
+    assert!(query_near_rescue(&app, &older, 3600).is_empty());
+}
+
+#[test]
+fn test_memo_round_trip() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg {
+        memo: Some("order-42/session-abc".to_string()),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let response = instantiate_escrow_with_response(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    assert_eq!(wasm_attribute(&response, "memo"), Some("order-42/session-abc".to_string()));
+
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    assert_eq!(query_escrow(&app, &contract_addr).memo, Some("order-42/session-abc".to_string()));
+    let escrows: EscrowsResponse = app
+        .wrap()
+        .query_wasm_smart(&contract_addr, &QueryMsg::EscrowsByMaker { maker: "maker".to_string(), start_after: None, limit: None })
+        .unwrap();
+    assert_eq!(escrows.escrows[0].memo, Some("order-42/session-abc".to_string()));
+
+    // No memo, no attribute
+    let response = instantiate_escrow_with_response(&mut app, &test_instantiate_msg(EscrowType::Source), &[Coin::new(1100, "uatom")]);
+    assert_eq!(wasm_attribute(&response, "memo"), None);
+}
+
+#[test]
+fn test_memo_length_capped() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+    let msg = InstantiateMsg {
+        memo: Some("m".repeat(257)),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &msg, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MemoTooLong { length: 257, max: 256 });
+
+    let msg = InstantiateMsg { memo: Some("m".repeat(256)), ..msg };
+    instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+}
+
+#[test]
+fn test_dst_amount_below_floor_rejected() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+    let msg = InstantiateMsg {
+        dst_amount: Uint128::new(1),
+        min_dst_amount: Some(Uint128::new(900)),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &msg, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAmount { amount: "dst amount 1 is below minimum 900".to_string() }
+    );
+
+    // The floor itself is accepted
+    let msg = InstantiateMsg { dst_amount: Uint128::new(900), ..msg };
+    instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+}
+
+#[test]
+fn test_withdraw_to_invalid_recipient_leaves_state_unchanged() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let before = load_escrow_state(&app, &contract_addr);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    let withdraw_msg = |recipient: &str| ExecuteMsg::WithdrawSrc {
+        escrow_id: 1,
+        secret: secret.clone(),
+        recipient: Some(recipient.to_string()),
+    };
+    let result = app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &withdraw_msg("Not An Address"), &[]);
+    assert!(result.is_err());
+    assert_eq!(load_escrow_state(&app, &contract_addr), before);
+    assert_eq!(app.wrap().query_balance(&contract_addr, "uatom").unwrap().amount, Uint128::new(1100));
+
+    app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &withdraw_msg("payout"), &[])
+        .unwrap();
+    assert_eq!(app.wrap().query_balance("payout", "uatom").unwrap().amount, Uint128::new(1000));
+}
+
+#[test]
+fn test_failed_deposit_send_reverts_cw20_transfer() {
+    let mut app = mock_app();
+    let token = instantiate_cw20(
+        &mut app,
+        vec![Cw20Coin { address: "owner".to_string(), amount: Uint128::new(5000) }],
+    );
+    let msg = InstantiateMsg {
+        token: token.to_string(),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(100, "uatom")]);
+    fund_cw20_escrow(&mut app, &token, &contract_addr, "owner", 1000);
+    let before = load_escrow_state(&app, &contract_addr);
+
+    // Lose the native deposit out from under the contract so the bank send
+    // after the CW20 principal transfer fails
+    app.init_modules(|router, _api, storage| router.bank.init_balance(storage, &contract_addr, vec![]))
+        .unwrap();
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    let result = app.execute_contract(
+        Addr::unchecked("taker"),
+        contract_addr.clone(),
+        &ExecuteMsg::CancelSrc { escrow_id: 1 },
+        &[],
+    );
+    assert!(result.is_err());
+
+    // The CW20 leg went through first but was rolled back with the rest
+    assert_eq!(query_cw20_balance(&app, &token, contract_addr.as_str()), Uint128::new(1000));
+    assert_eq!(query_cw20_balance(&app, &token, "maker"), Uint128::zero());
+    assert_eq!(load_escrow_state(&app, &contract_addr), before);
+}
+
+fn sign_mutual_cancel(signing_key: &SigningKey, contract_addr: &Addr) -> Binary {
+    let message_hash = mutual_cancel_hash(contract_addr, 1);
+    let signature: Signature = signing_key.sign_prehash(&message_hash).unwrap();
+    Binary::from(signature.to_bytes().to_vec())
+}
+
+#[test]
+fn test_mutual_cancel_before_withdrawal_window() {
+    let mut app = mock_app();
+    let (maker_key, maker_pubkey) = test_signing_key(7);
+    let (other_key, _) = test_signing_key(9);
+    let msg = InstantiateMsg {
+        maker_pubkey: Some(maker_pubkey),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    let mutual_cancel = |counterparty_sig| ExecuteMsg::MutualCancel { escrow_id: 1, counterparty_sig };
+
+    // Only the parties may submit
+    let err = app
+        .execute_contract(Addr::unchecked("relayer"), contract_addr.clone(), &mutual_cancel(sign_mutual_cancel(&maker_key, &contract_addr)), &[])
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized { .. }));
+
+    // The signature must come from the registered counterparty key, over this contract
+    for counterparty_sig in [
+        sign_mutual_cancel(&other_key, &contract_addr),
+        sign_mutual_cancel(&maker_key, &Addr::unchecked("other_contract")),
+        sign_cancellation(&maker_key, &contract_addr, &msg.order_hash),
+    ] {
+        let err = app
+            .execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &mutual_cancel(counterparty_sig), &[])
+            .unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSignature {});
+    }
+
+    // The maker can't supply its own signature, the taker registered no key to sign with
+    let err = app
+        .execute_contract(Addr::unchecked("maker"), contract_addr.clone(), &mutual_cancel(sign_mutual_cancel(&maker_key, &contract_addr)), &[])
+        .unwrap_err();
+    assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSignature {});
+
+    // Withdrawal hasn't opened yet, but agreement doesn't wait for the timelocks
+    app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &mutual_cancel(sign_mutual_cancel(&maker_key, &contract_addr)), &[])
+        .unwrap();
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Cancelled);
+    assert_eq!(app.wrap().query_balance("maker", "uatom").unwrap().amount, Uint128::new(2000 + 1000));
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(2000));
+    assert_eq!(app.wrap().query_balance("owner", "uatom").unwrap().amount, Uint128::new(10000 - 1100 + 100));
+}
+
+#[test]
+fn test_mutual_cancel_destination_submitted_by_maker() {
+    let mut app = mock_app();
+    let (taker_key, taker_pubkey) = test_signing_key(8);
+    let msg = InstantiateMsg {
+        taker_pubkey: Some(taker_pubkey),
+        ..test_instantiate_msg(EscrowType::Destination)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    app.execute_contract(
+        Addr::unchecked("maker"),
+        contract_addr.clone(),
+        &ExecuteMsg::MutualCancel { escrow_id: 1, counterparty_sig: sign_mutual_cancel(&taker_key, &contract_addr) },
+        &[],
+    )
+    .unwrap();
+
+    // Destination principal goes back to the taker, the deposit to the creator
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Cancelled);
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(2000 + 1000));
+    assert_eq!(app.wrap().query_balance("owner", "uatom").unwrap().amount, Uint128::new(10000 - 1100 + 100));
+    assert_eq!(app.wrap().query_balance("maker", "uatom").unwrap().amount, Uint128::new(2000));
+}
+
+#[test]
+fn test_max_lifetime_bounds_final_stage_plus_rescue_delay() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+
+    // Source: public cancellation at 4h plus a rescue delay filling the rest of the cap
+    let at_max = InstantiateMsg {
+        rescue_delay_override: Some(MAX_LIFETIME_SECONDS - 4 * 3600),
+        admin_rescue_delay: MAX_LIFETIME_SECONDS,
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    instantiate_escrow(&mut app, &at_max, &[Coin::new(1100, "uatom")]);
+
+    let above_max = InstantiateMsg { rescue_delay_override: Some(MAX_LIFETIME_SECONDS - 4 * 3600 + 1), ..at_max };
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &above_max, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTime {
+            reason: format!(
+                "Lifetime {}s up to SrcPublicCancellation plus rescue delay exceeds maximum {MAX_LIFETIME_SECONDS}s",
+                MAX_LIFETIME_SECONDS + 1
+            )
+        }
+    );
+
+    // Destination ends at its 3h cancellation stage
+    let dst = InstantiateMsg {
+        rescue_delay_override: Some(MAX_LIFETIME_SECONDS - 3 * 3600),
+        admin_rescue_delay: MAX_LIFETIME_SECONDS,
+        ..test_instantiate_msg(EscrowType::Destination)
+    };
+    instantiate_escrow(&mut app, &dst, &[Coin::new(1100, "uatom")]);
+
+    let dst = InstantiateMsg { rescue_delay_override: Some(MAX_LIFETIME_SECONDS - 3 * 3600 + 1), ..dst };
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &dst, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidTime { .. }));
+}
+
+fn mock_escrows_by_height(deps: &MockDeps, from_height: u64, to_height: u64, start_after: Option<u64>) -> Vec<u64> {
+    let msg = QueryMsg::EscrowsByHeight { from_height, to_height, start_after, limit: Some(2) };
+    let raw = escrow_contract::query(deps.as_ref(), cosmwasm_std::testing::mock_env(), msg).unwrap();
+    let response: EscrowsResponse = cosmwasm_std::from_json(raw).unwrap();
+    response.escrows.into_iter().map(|escrow| escrow.escrow_id).collect()
+}
+
+#[test]
+fn test_escrows_by_height_filters_range() {
+    // Escrows 2..=5 stored alongside the instantiated one, ten blocks apart
+    let mut deps = mock_escrow_deps();
+    let escrow_state = ESCROWS.load(&deps.storage, 1).unwrap();
+    let base_height = escrow_state.escrow_info.created_at_height;
+    for escrow_id in 2..=5 {
+        let mut other = escrow_state.clone();
+        other.escrow_info.created_at_height = base_height + 10 * (escrow_id - 1);
+        create_escrow(&mut deps.storage, escrow_id, &other).unwrap();
+    }
+
+    // Inclusive bounds, paginated with the limit of two
+    assert_eq!(mock_escrows_by_height(&deps, base_height + 10, base_height + 30, None), vec![2, 3]);
+    assert_eq!(mock_escrows_by_height(&deps, base_height + 10, base_height + 30, Some(3)), vec![4]);
+    assert_eq!(mock_escrows_by_height(&deps, base_height + 11, base_height + 29, None), vec![3]);
+    assert!(mock_escrows_by_height(&deps, base_height + 41, base_height + 100, None).is_empty());
+}
+
+#[test]
+fn test_escrows_by_height_matches_creation_block() {
+    let mut app = mock_app();
+    let contract_addr = instantiate_escrow(
+        &mut app,
+        &test_instantiate_msg(EscrowType::Source),
+        &[Coin::new(1100, "uatom")],
+    );
+    let created_at_height = app.block_info().height;
+    app.update_block(|block| block.height += 100);
+
+    let by_height = |from_height, to_height| -> EscrowsResponse {
+        app.wrap()
+            .query_wasm_smart(&contract_addr, &QueryMsg::EscrowsByHeight { from_height, to_height, start_after: None, limit: None })
+            .unwrap()
+    };
+    let escrows = by_height(created_at_height, created_at_height).escrows;
+    assert_eq!(escrows.len(), 1);
+    assert_eq!(escrows[0].created_at_height, created_at_height);
+    assert!(by_height(created_at_height + 1, created_at_height + 100).escrows.is_empty());
+}
+
+#[test]
+fn test_finalize_with_nothing_to_transfer_rejected() {
+    let mut deps = mock_escrow_deps();
+    let mut escrow_state = ESCROWS.load(&deps.storage, 1).unwrap();
+    escrow_state.balance = Uint128::zero();
+    escrow_state.native_balance = Uint128::zero();
+    ESCROWS.save(&mut deps.storage, 1, &escrow_state).unwrap();
+
+    let mut env = cosmwasm_std::testing::mock_env();
+    env.block.time = env.block.time.plus_seconds(3 * 3600);
+    let err = escrow_contract::execute(
+        deps.as_mut(),
+        env.clone(),
+        cosmwasm_std::testing::mock_info("taker", &[]),
+        ExecuteMsg::CancelSrc { escrow_id: 1 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::OperationFailed { reason: "nothing to transfer".to_string() });
+    assert_eq!(ESCROWS.load(&deps.storage, 1).unwrap().escrow_info.status, EscrowStatus::Active);
+
+    // Rescue still closes it out
+    env.block.time = env.block.time.plus_seconds(86400);
+    let response = escrow_contract::execute(
+        deps.as_mut(),
+        env,
+        cosmwasm_std::testing::mock_info("taker", &[]),
+        ExecuteMsg::Rescue { escrow_id: 1 },
+    )
+    .unwrap();
+    assert!(response.messages.is_empty());
+    assert_eq!(ESCROWS.load(&deps.storage, 1).unwrap().escrow_info.status, EscrowStatus::Rescued);
+}
+
+#[test]
+fn test_crowdfunded_deposit_refunded_pro_rata_on_cancel() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg {
+        deposit_contributors: Some(vec![
+            ("backer_a".to_string(), Uint128::new(60)),
+            ("backer_b".to_string(), Uint128::new(40)),
+        ]),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    app.execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &ExecuteMsg::CancelSrc { escrow_id: 1 }, &[])
+        .unwrap();
+
+    // Principal back to the maker, each backer gets its share instead of the taker
+    assert_eq!(app.wrap().query_balance("maker", "uatom").unwrap().amount, Uint128::new(2000 + 1000));
+    assert_eq!(app.wrap().query_balance("backer_a", "uatom").unwrap().amount, Uint128::new(60));
+    assert_eq!(app.wrap().query_balance("backer_b", "uatom").unwrap().amount, Uint128::new(40));
+    assert_eq!(app.wrap().query_balance("taker", "uatom").unwrap().amount, Uint128::new(2000));
+    assert!(app.wrap().query_all_balances(&contract_addr).unwrap().is_empty());
+}
+
+#[test]
+fn test_deposit_contributions_must_cover_deposit() {
+    let mut app = mock_app();
+    let contract_id = app.store_code(escrow_contract());
+    let msg = InstantiateMsg {
+        deposit_contributors: Some(vec![
+            ("backer_a".to_string(), Uint128::new(60)),
+            ("backer_b".to_string(), Uint128::new(30)),
+        ]),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let err = app
+        .instantiate_contract(contract_id, Addr::unchecked("owner"), &msg, &[Coin::new(1100, "uatom")], "Escrow", None)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAmount {
+            amount: "deposit contributions total 90 across 2 contributors, must be non-zero shares of 100".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_solvency_check_trips_on_native_drift() {
+    let mut app = mock_app();
+    let msg = InstantiateMsg { check_solvency: true, ..test_instantiate_msg(EscrowType::Source) };
+    let contract_addr = instantiate_escrow(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+
+    // Books say 1100 (principal plus deposit), the contract only holds 600
+    app.init_modules(|router, _api, storage| {
+        router.bank.init_balance(storage, &contract_addr, vec![Coin::new(600, "uatom")])
+    })
+    .unwrap();
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3 * 3600));
+    let err = app
+        .execute_contract(Addr::unchecked("taker"), contract_addr.clone(), &ExecuteMsg::CancelSrc { escrow_id: 1 }, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientBalance { required: "1100uatom".to_string(), available: "600uatom".to_string() }
+    );
+    assert_eq!(query_escrow(&app, &contract_addr).status, EscrowStatus::Active);
+}
+
+#[test]
+fn test_solvency_check_trips_on_cw20_drift() {
+    // Mock storage and querier, so the token contract can report less than was delivered
+    let mut deps = cosmwasm_std::testing::mock_dependencies();
+    let mut env = cosmwasm_std::testing::mock_env();
+    let msg = InstantiateMsg {
+        token: "token".to_string(),
+        check_solvency: true,
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    escrow_contract::instantiate(deps.as_mut(), env.clone(), cosmwasm_std::testing::mock_info("owner", &[Coin::new(100, "uatom")]), msg)
+        .unwrap();
+    let hook = ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+        sender: "owner".to_string(),
+        amount: Uint128::new(1000),
+        msg: to_json_binary(&Cw20HookMsg::DeployEscrow { escrow_id: 1 }).unwrap(),
+    });
+    escrow_contract::execute(deps.as_mut(), env.clone(), cosmwasm_std::testing::mock_info("token", &[]), hook)
+        .unwrap();
+
+    let held = |balance: u128| {
+        move |_: &cosmwasm_std::WasmQuery| {
+            let response = BalanceResponse { balance: Uint128::new(balance) };
+            cosmwasm_std::SystemResult::Ok(cosmwasm_std::ContractResult::Ok(to_json_binary(&response).unwrap()))
+        }
+    };
+    deps.querier.update_balance(env.contract.address.clone(), vec![Coin::new(100, "uatom")]);
+    deps.querier.update_wasm(held(600));
+    env.block.time = env.block.time.plus_seconds(3 * 3600);
+    let cancel = || ExecuteMsg::CancelSrc { escrow_id: 1 };
+
+    // Only part of the booked CW20 principal is actually held
+    let err = escrow_contract::execute(deps.as_mut(), env.clone(), cosmwasm_std::testing::mock_info("taker", &[]), cancel())
+        .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientBalance { required: "1000token".to_string(), available: "600token".to_string() });
+
+    // Once the shortfall is made up the cancellation goes through
+    deps.querier.update_wasm(held(1000));
+    escrow_contract::execute(deps.as_mut(), env, cosmwasm_std::testing::mock_info("taker", &[]), cancel())
+        .unwrap();
+    assert_eq!(ESCROWS.load(&deps.storage, 1).unwrap().escrow_info.status, EscrowStatus::Cancelled);
+}
+
+#[test]
+fn test_event_prefix_applied_to_method_attribute() {
+    let mut app = mock_app();
+    let secret = generate_secret();
+    let msg = InstantiateMsg {
+        hashlock: hash_secret(&secret),
+        event_prefix: Some("desk_a.".to_string()),
+        ..test_instantiate_msg(EscrowType::Source)
+    };
+    let response = instantiate_escrow_with_response(&mut app, &msg, &[Coin::new(1100, "uatom")]);
+    assert_eq!(wasm_attribute(&response, "method"), Some("desk_a.instantiate".to_string()));
+    let contract_addr = Addr::unchecked(wasm_attribute(&response, "_contract_address").unwrap());
+
+    let deployed_at = app.block_info().time;
+    app.update_block(|block| block.time = deployed_at.plus_seconds(3600));
+    let response = app
+        .execute_contract(
+            Addr::unchecked("taker"),
+            contract_addr,
+            &ExecuteMsg::WithdrawSrc { escrow_id: 1, secret, recipient: None },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(wasm_attribute(&response, "method"), Some("desk_a.withdraw_src".to_string()));
+
+    // No prefix leaves the attribute as it was
+    let response = instantiate_escrow_with_response(&mut app, &test_instantiate_msg(EscrowType::Source), &[Coin::new(1100, "uatom")]);
+    assert_eq!(wasm_attribute(&response, "method"), Some("instantiate".to_string()));
+}